@@ -1,28 +1,66 @@
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, ProxyCw20HookMsg, QueryMsg};
+use crate::migration::{BONDED_REWARDS_DETAILSV100, CONFIGV100};
+use crate::msg::{
+    AllNftInfoResponse, Approval, BondCapacityConfig, BondCapacityInfo, BondCapacityResponse,
+    BondStatisticsInfo, BondStatisticsResponse, BondType, ContractInfoResponse, Cw721ReceiveMsg,
+    EarlyAccessEntry, EarlyAccessResponse, ExecuteMsg, FeeCollector, FeeRecipient, InstantiateMsg,
+    LegacyBondType, MaturityHistogramEntry, MigrateMsg, NftInfoResponse, NumTokensResponse,
+    OwnerOfResponse, PairResponse, PairSettings, PauseStatus, PenaltyDestination,
+    ProtocolLiquidityResponse, ProxyCw20HookMsg, QueryMsg, ReferralRewardsResponse,
+    SimulateBondResponse, SimulateSwapResponse, SwapLimits, TokensResponse, UpdateConfigMsg,
+    VestingSchedule,
+};
 use crate::state::{
-    BondedRewardsDetails, Config, ContractVersion, SubMessageDetails, SubMessageNextAction,
-    SubMessageType, BONDED_REWARDS_DETAILS, CONFIG, CONTRACT, SUB_MESSAGE_DETAILS, SUB_REQ_ID,
+    load_config, read_all_bonds, read_maturity_histogram, read_owner_bond_ids,
+    read_pending_requests, read_pre_opening_maturities, read_referral_rewards, read_user_bonds,
+    BondCapacityUsage, BondTerm, BondedRewardsDetails, Config, ContractVersion,
+    EarlyAccessAllowance, MaturityBucket, PendingRequest, PendingRequestStatus, ProxyRewardDetails,
+    RouteSwapDetails, SubMessageDetails, SubMessageNextAction, SubMessageType, SwapActivity,
+    SwapProceedsDetails, SwapRecord, WithdrawLiquidityDetails, BONDED_REWARDS_DETAILS,
+    BOND_APPROVALS, BOND_CAPACITY_USAGE, BOND_ID, BOND_OPERATORS, BOND_OWNERS, BOND_STATS,
+    BOND_TERMS, BOND_TERM_ID, CONFIG, CONTRACT, EARLY_ACCESS_ALLOWANCES, MATURITY_HISTOGRAM,
+    OWNERSHIP_PROPOSAL, PAIRS, PAUSE_STATUS, PENDING_REQUESTS, PENDING_REQUEST_ID,
+    PRE_OPENING_MATURITIES, PROTOCOL_LIQUIDITY, REFERRAL_REWARDS, SECONDS_PER_DAY,
+    SUB_MESSAGE_DETAILS, SUB_REQ_ID, SWAP_ACTIVITY,
 };
 use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo, PairInfo};
+use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use astroport::generator::{
+    Cw20HookMsg as GeneratorCw20HookMsg, ExecuteMsg as GeneratorExecuteMsg, PendingTokenResponse,
+    QueryMsg as GeneratorQueryMsg, RewardInfoResponse,
+};
+use astroport::oracle::QueryMsg as OracleQueryMsg;
 use astroport::pair::ExecuteMsg as PairExecuteMsg;
 use astroport::pair::QueryMsg::{CumulativePrices, Pair, Pool, ReverseSimulation, Simulation};
 use astroport::pair::{
     CumulativePricesResponse, Cw20HookMsg, PoolResponse, ReverseSimulationResponse,
     SimulationResponse,
 };
+use astroport::querier::query_token_balance;
+use astroport::router::{
+    Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg, SwapOperation,
+};
 
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
-    Decimal, Deps, DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult,
-    Storage, SubMsg, Timestamp, Uint128, Uint64, WasmMsg,
+    entry_point, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, ContractResult,
+    CosmosMsg, Decimal, Deps, DepsMut, Env, Event, Fraction, MessageInfo, Order, QuerierWrapper,
+    Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, Uint64,
+    WasmMsg,
+};
+use cw20::{
+    AllowanceResponse, BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, Expiration,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, U64Key};
+use sha2::{Digest, Sha256};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-proxy";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Name of the CW721 tokens representing the bonds.
+const BOND_TOKEN_NAME: &str = "Fury Bond";
+/// Symbol of the CW721 tokens representing the bonds.
+const BOND_TOKEN_SYMBOL: &str = "FURYBOND";
 
 const FURY_PROVIDED: bool = true;
 const NO_FURY_PROVIDED: bool = false;
@@ -75,16 +113,190 @@ pub fn instantiate(
         platform_fees: msg.platform_fees,
         transaction_fees: msg.transaction_fees,
         swap_fees: msg.swap_fees,
-		max_bonding_limit_per_user: msg.max_bonding_limit_per_user,
+        max_bonding_limit_per_user: msg.max_bonding_limit_per_user,
+        pair_vesting_schedule: msg.pair_vesting_schedule.unwrap_or_default(),
+        native_vesting_schedule: msg.native_vesting_schedule.unwrap_or_default(),
+        oracle_address: None,
+        max_twap_deviation: msg.max_twap_deviation,
+        pair_bond_capacity: msg.pair_bond_capacity.unwrap_or_default(),
+        native_bond_capacity: msg.native_bond_capacity.unwrap_or_default(),
+        bonds_transferable: msg.bonds_transferable.unwrap_or(false),
+        early_unbond_penalty_rate: msg.early_unbond_penalty_rate,
+        early_unbond_penalty_destination: validate_penalty_destination(
+            deps.as_ref(),
+            msg.early_unbond_penalty_destination.unwrap_or_default(),
+        )?,
+        quote_asset: msg.quote_asset.unwrap_or(AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        }),
+        fury_fee_discount: msg.fury_fee_discount.unwrap_or_default(),
+        fee_collectors: validate_fee_collectors(
+            deps.as_ref(),
+            msg.fee_collectors.unwrap_or_default(),
+        )?,
+        maker_address: None,
+        router_address: None,
+        guardian_address: None,
+        referral_fee_share: msg.referral_fee_share.unwrap_or_default(),
+        early_access_window_in_sec: msg.early_access_window_in_sec.unwrap_or_default(),
+        early_access_merkle_root: msg.early_access_merkle_root,
+        swap_limits: msg.swap_limits.unwrap_or_default(),
+        generator_address: None,
     };
+    cfg.quote_asset.check(deps.api)?;
+    if let Some(maker_address) = msg.maker_address {
+        cfg.maker_address = Some(addr_validate_to_lower(deps.api, &maker_address)?);
+    }
+    if let Some(router_address) = msg.router_address {
+        cfg.router_address = Some(addr_validate_to_lower(deps.api, &router_address)?);
+    }
+    if let Some(guardian_address) = msg.guardian_address {
+        cfg.guardian_address = Some(addr_validate_to_lower(deps.api, &guardian_address)?);
+    }
+    if let Some(generator_address) = msg.generator_address {
+        cfg.generator_address = Some(addr_validate_to_lower(deps.api, &generator_address)?);
+    }
+    if let Some(oracle_address) = msg.oracle_address {
+        cfg.oracle_address = Some(addr_validate_to_lower(deps.api, &oracle_address)?);
+    }
     if let Some(pool_pair_addr) = msg.pool_pair_address {
         cfg.pool_pair_address = pool_pair_addr;
     }
+    validate_config(&cfg)?;
     CONFIG.save(deps.storage, &cfg)?;
     // configure_proxy(deps, env, info, None, msg.swap_opening_date)?;
     Ok(Response::default())
 }
 
+/// Migrates the storage of an older version of the proxy. Settings added since that version
+/// take the same defaults as on instantiation. Bonds of version 1.0.0 can be moved in batches,
+/// migrating again to the current version moves the next batch.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    let legacy_bonds_left = match contract_version.contract.as_ref() {
+        CONTRACT_NAME => match contract_version.version.as_ref() {
+            "1.0.0" => {
+                let config_v100 = CONFIGV100.load(deps.storage)?;
+                let config = Config {
+                    admin_address: config_v100.admin_address,
+                    custom_token_address: config_v100.custom_token_address,
+                    pair_discount_rate: config_v100.pair_discount_rate,
+                    pair_bonding_period_in_sec: config_v100.pair_bonding_period_in_sec,
+                    pair_fury_reward_wallet: config_v100.pair_fury_reward_wallet,
+                    pair_lp_tokens_holder: config_v100.pair_lp_tokens_holder,
+                    native_discount_rate: config_v100.native_discount_rate,
+                    native_bonding_period_in_sec: config_v100.native_bonding_period_in_sec,
+                    native_investment_reward_wallet: config_v100.native_investment_reward_wallet,
+                    native_investment_receive_wallet: config_v100.native_investment_receive_wallet,
+                    authorized_liquidity_provider: config_v100.authorized_liquidity_provider,
+                    swap_opening_date: config_v100.swap_opening_date,
+                    pool_pair_address: config_v100.pool_pair_address,
+                    liquidity_token: config_v100.liquidity_token,
+                    platform_fees_collector_wallet: config_v100.platform_fees_collector_wallet,
+                    platform_fees: config_v100.platform_fees,
+                    transaction_fees: config_v100.transaction_fees,
+                    swap_fees: config_v100.swap_fees,
+                    max_bonding_limit_per_user: config_v100.max_bonding_limit_per_user,
+                    pair_vesting_schedule: VestingSchedule::default(),
+                    native_vesting_schedule: VestingSchedule::default(),
+                    oracle_address: None,
+                    max_twap_deviation: None,
+                    pair_bond_capacity: BondCapacityConfig::default(),
+                    native_bond_capacity: BondCapacityConfig::default(),
+                    bonds_transferable: false,
+                    early_unbond_penalty_rate: None,
+                    early_unbond_penalty_destination: PenaltyDestination::default(),
+                    quote_asset: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    fury_fee_discount: 0,
+                    fee_collectors: vec![],
+                    maker_address: None,
+                    router_address: None,
+                    guardian_address: None,
+                    referral_fee_share: 0,
+                    early_access_window_in_sec: 0,
+                    early_access_merkle_root: None,
+                    swap_limits: SwapLimits::default(),
+                    generator_address: None,
+                };
+                CONFIG.save(deps.storage, &config)?;
+                migrate_bonds_v100(deps.storage, &msg.legacy_bond_types, msg.limit)?
+            }
+            CONTRACT_VERSION => {
+                migrate_bonds_v100(deps.storage, &msg.legacy_bond_types, msg.limit)?
+            }
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    };
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION)
+        .add_attribute("legacy_bonds_left", legacy_bonds_left.to_string()))
+}
+
+/// Moves the bonds of version 1.0.0, stored as a list per user, to the map keyed by bond id.
+/// Each bond gets a new id and the type given for its bonding period. Returns whether bonds of
+/// other users are left to move
+fn migrate_bonds_v100(
+    storage: &mut dyn Storage,
+    legacy_bond_types: &[LegacyBondType],
+    limit: Option<u32>,
+) -> Result<bool, ContractError> {
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+    let users = BONDED_REWARDS_DETAILSV100
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit)
+        .map(|key| String::from_utf8(key).map_err(StdError::from))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    for user_address in users {
+        let legacy_bonds = BONDED_REWARDS_DETAILSV100.load(storage, user_address.clone())?;
+        for legacy_bond in legacy_bonds {
+            if legacy_bond.bonded_amount.is_zero() {
+                continue;
+            }
+            let bond_type = legacy_bond_types
+                .iter()
+                .find(|legacy_type| legacy_type.bonding_period == legacy_bond.bonding_period)
+                .map(|legacy_type| legacy_type.bond_type)
+                .ok_or(ContractError::UnknownLegacyBondPeriod {
+                    bonding_period: legacy_bond.bonding_period,
+                })?;
+            let bond_id = BOND_ID.may_load(storage)?.unwrap_or_default() + 1;
+            BOND_ID.save(storage, &bond_id)?;
+            let bond = BondedRewardsDetails {
+                bond_id,
+                user_address: user_address.clone(),
+                bond_type,
+                bonded_amount: legacy_bond.bonded_amount,
+                bonding_period: legacy_bond.bonding_period,
+                bonding_start_timestamp: legacy_bond.bonding_start_timestamp,
+                vesting_schedule: VestingSchedule::Cliff,
+                claimed_amount: Uint128::zero(),
+                term_id: None,
+            };
+            // The UST raised by these bonds was not recorded
+            record_bond_issued(storage, &bond, Uint128::zero())?;
+            BONDED_REWARDS_DETAILS.save(storage, (&user_address, U64Key::new(bond_id)), &bond)?;
+            BOND_OWNERS.save(storage, U64Key::new(bond_id), &user_address)?;
+        }
+        BONDED_REWARDS_DETAILSV100.remove(storage, user_address);
+    }
+    Ok(BONDED_REWARDS_DETAILSV100
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_some())
+}
+
 /// set_contract_version should be used in instantiate to store the original version, and after a successful
 /// migrate to update it
 pub fn set_contract_version<T: Into<String>, U: Into<String>>(
@@ -99,6 +311,11 @@ pub fn set_contract_version<T: Into<String>, U: Into<String>>(
     CONTRACT.save(store, &val)
 }
 
+/// get_contract_version can be used in migrate to read the previous version of this contract
+pub fn get_contract_version(store: &dyn Storage) -> StdResult<ContractVersion> {
+    CONTRACT.load(store)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -119,6 +336,108 @@ pub fn execute(
             liquidity_token,
             swap_opening_date,
         ),
+        ExecuteMsg::UpdateConfig(update_config_msg) => {
+            update_config(deps, info, *update_config_msg)
+        }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            propose_new_owner(
+                deps,
+                info,
+                env,
+                owner,
+                expires_in,
+                config.admin_address,
+                OWNERSHIP_PROPOSAL,
+            )
+            .map_err(|e| e.into())
+        }
+        ExecuteMsg::DropOwnershipProposal {} => {
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            drop_ownership_proposal(deps, info, config.admin_address, OWNERSHIP_PROPOSAL)
+                .map_err(|e| e.into())
+        }
+        ExecuteMsg::ClaimOwnership {} => {
+            claim_ownership(deps, info, env, OWNERSHIP_PROPOSAL, |deps, new_owner| {
+                CONFIG.update::<_, StdError>(deps.storage, |mut v| {
+                    v.admin_address = new_owner;
+                    Ok(v)
+                })?;
+
+                Ok(())
+            })
+            .map_err(|e| e.into())
+        }
+        ExecuteMsg::AddBondTerm {
+            bond_type,
+            bonding_period_in_sec,
+            discount_rate,
+            vesting_schedule,
+        } => add_bond_term(
+            deps,
+            info,
+            bond_type,
+            bonding_period_in_sec,
+            discount_rate,
+            vesting_schedule,
+        ),
+        ExecuteMsg::RetireBondTerm { term_id } => retire_bond_term(deps, info, term_id),
+        ExecuteMsg::SetPair { pair_id, settings } => set_pair(deps, info, pair_id, settings),
+        ExecuteMsg::RemovePair { pair_id } => remove_pair(deps, info, pair_id),
+        ExecuteMsg::SetPauseStatus {
+            swaps,
+            pair_bonding,
+            native_bonding,
+            claims,
+            withdrawals,
+        } => set_pause_status(
+            deps,
+            info,
+            swaps,
+            pair_bonding,
+            native_bonding,
+            claims,
+            withdrawals,
+        ),
+        ExecuteMsg::EarlyUnbond {
+            bond_id,
+            pay_fees_in_fury,
+        } => {
+            if load_pause_status(deps.storage)?.claims {
+                return Err(ContractError::ClaimsPaused {});
+            }
+            early_unbond(deps, env, info, bond_id, pay_fees_in_fury.unwrap_or(false))
+        }
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => transfer_nft(deps, env, info, recipient, token_id),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => send_nft(deps, env, info, contract, token_id, msg),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => approve(deps, env, info, spender, token_id, expires),
+        ExecuteMsg::Revoke { spender, token_id } => revoke(deps, env, info, spender, token_id),
+        ExecuteMsg::ApproveAll { operator, expires } => {
+            approve_all(deps, env, info, operator, expires)
+        }
+        ExecuteMsg::RevokeAll { operator } => revoke_all(deps, info, operator),
+        ExecuteMsg::UpdateEarlyAccessAllowlist { add, remove } => {
+            update_early_access_allowlist(deps, info, add, remove)
+        }
+        ExecuteMsg::RegisterEarlyAccess { cap, proof } => {
+            register_early_access(deps, info, cap, proof)
+        }
+        ExecuteMsg::CancelPendingRequest { request_id } => {
+            cancel_pending_request(deps, env, info, request_id)
+        }
         ExecuteMsg::Receive(received_message) => {
             process_received_message(deps, env, info, received_message)
         }
@@ -126,8 +445,9 @@ pub fn execute(
             assets,
             slippage_tolerance,
             auto_stake,
+            pair_id,
         } => {
-            let config = CONFIG.load(deps.storage)?;
+            let config = load_config(deps.storage, pair_id.as_deref())?;
             if info.sender != config.authorized_liquidity_provider {
                 return Err(ContractError::Unauthorized {});
             }
@@ -142,6 +462,9 @@ pub fn execute(
                 auto_stake,
                 receiver,
                 SubMessageNextAction::IncreaseAllowance,
+                Uint128::zero(),
+                None,
+                pair_id,
                 None,
             )
         }
@@ -149,32 +472,46 @@ pub fn execute(
             assets,
             slippage_tolerance,
             auto_stake,
+            term_id,
+            pair_id,
+            referrer,
         } => {
+            if load_pause_status(deps.storage)?.pair_bonding {
+                return Err(ContractError::PairBondingPaused {});
+            }
+            let referrer = validate_referrer(deps.api, &info.sender, referrer)?;
+            if let Some(term_id) = term_id {
+                load_active_bond_term(deps.storage, term_id, BondType::Pair)?;
+            }
             let required_ust_fees: Uint128;
             required_ust_fees = query_platform_fees(
                 deps.as_ref(),
+                env.clone(),
                 to_binary(&ExecuteMsg::ProvidePairForReward {
                     assets: assets.clone(),
                     slippage_tolerance: slippage_tolerance.clone(),
                     auto_stake: auto_stake.clone(),
+                    term_id: term_id,
+                    pair_id: pair_id.clone(),
+                    referrer: referrer.as_ref().map(|referrer| referrer.to_string()),
                 })?,
+                Some(info.sender.to_string()),
             )?;
-            let mut fees = Uint128::zero();
-            for fund in info.funds.clone() {
-                if fund.denom == "uusd" {
-                    fees = fees.checked_add(fund.amount).unwrap();
-                }
-            }
-            let mut native_tax = Uint128::zero();
-            for asset in assets.clone() {
-                if asset.is_native_token() {
-                    fees = fees.checked_sub(asset.amount).unwrap();
-                    native_tax = native_tax
-                        .checked_add(asset.compute_tax(&deps.querier)?)
-                        .unwrap();
+            let config = load_config(deps.storage, pair_id.as_deref())?;
+            let mut fees = received_platform_fees(&config, &info.funds, required_ust_fees);
+            if config.quote_asset.is_native_token() {
+                // The quote side of the pair is attached along with the fees
+                let mut native_tax = Uint128::zero();
+                for asset in assets.clone() {
+                    if asset.info == config.quote_asset {
+                        fees = fees.checked_sub(asset.amount).unwrap();
+                        native_tax = native_tax
+                            .checked_add(asset.compute_tax(&deps.querier)?)
+                            .unwrap();
+                    }
                 }
+                fees = fees.checked_sub(native_tax).unwrap();
             }
-            fees = fees.checked_sub(native_tax).unwrap();
             if fees < required_ust_fees {
                 return Err(ContractError::InsufficientFees {
                     required: required_ust_fees,
@@ -182,28 +519,26 @@ pub fn execute(
                 });
             }
             let mut info_to_send = info.clone();
-            let mut platform_fee_funds = Coin::new(0, format!("uusd"));
-            if !fees.is_zero() {
-                //Received the platform fees, remove it from funds
-                let mut coin_to_set_in_funds = Coin::new(0, format!("uusd"));
-                for coin in info.funds.clone() {
-                    if coin.denom == "uusd" {
-                        coin_to_set_in_funds = Coin {
-                            amount: coin.amount - required_ust_fees,
-                            denom: coin.denom.clone(),
-                        };
-                        platform_fee_funds = Coin {
-                            amount: required_ust_fees,
-                            denom: coin.denom.clone(),
-                        };
+            if let AssetInfo::NativeToken { denom } = &config.quote_asset {
+                if !fees.is_zero() {
+                    //Received the platform fees, remove it from funds
+                    let mut coin_to_set_in_funds = Coin::new(0, denom.clone());
+                    for coin in info.funds.clone() {
+                        if &coin.denom == denom {
+                            coin_to_set_in_funds = Coin {
+                                amount: coin.amount - required_ust_fees,
+                                denom: coin.denom.clone(),
+                            };
+                        }
                     }
+                    info_to_send.funds = vec![coin_to_set_in_funds];
                 }
-                info_to_send.funds = vec![coin_to_set_in_funds];
-                // and transfer them to
             }
-            let config = CONFIG.load(deps.storage)?;
-            let receiver: Option<String>;
-            receiver = Some(config.pair_lp_tokens_holder.to_string());
+            // The proxy receives the LP tokens to stake them itself when a generator is configured
+            let (receiver, auto_stake) = match config.generator_address {
+                Some(_) => (Some(env.contract.address.to_string()), None),
+                None => (Some(config.pair_lp_tokens_holder.to_string()), auto_stake),
+            };
             provide_liquidity(
                 deps,
                 env,
@@ -213,26 +548,58 @@ pub fn execute(
                 auto_stake,
                 receiver,
                 SubMessageNextAction::TransferCustomAssetsFromFundsOwner,
-                Some(platform_fee_funds),
+                required_ust_fees,
+                term_id,
+                pair_id,
+                referrer,
             )
         }
         ExecuteMsg::ProvideNativeForReward {
             asset,
             slippage_tolerance,
             auto_stake,
+            term_id,
+            pair_id,
+            referrer,
         } => {
-            if !asset.is_native_token() {
+            if load_pause_status(deps.storage)?.native_bonding {
+                return Err(ContractError::NativeBondingPaused {});
+            }
+            let referrer = validate_referrer(deps.api, &info.sender, referrer)?;
+            let config = load_config(deps.storage, pair_id.as_deref())?;
+            if asset.info != config.quote_asset {
                 return Err(ContractError::Unauthorized {});
             }
-            provide_native_liquidity(deps, env, info, asset, slippage_tolerance, auto_stake)
+            if let Some(term_id) = term_id {
+                load_active_bond_term(deps.storage, term_id, BondType::Native)?;
+            }
+            provide_native_liquidity(
+                deps,
+                env,
+                info,
+                asset,
+                slippage_tolerance,
+                auto_stake,
+                term_id,
+                pair_id,
+                referrer,
+            )
         }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            pay_fees_in_fury,
+            deduct_fees_from_proceeds,
+            pair_id,
+            referrer,
         } => {
+            if load_pause_status(deps.storage)?.swaps {
+                return Err(ContractError::SwapsPaused {});
+            }
             offer_asset.info.check(deps.api)?;
+            let referrer = validate_referrer(deps.api, &info.sender, referrer)?;
 
             let to_addr = if let Some(to_addr) = to {
                 Some(addr_validate_to_lower(deps.api, &to_addr)?)
@@ -248,13 +615,130 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                pay_fees_in_fury.unwrap_or(false),
+                deduct_fees_from_proceeds.unwrap_or(false),
+                pair_id,
+                referrer,
+            )
+        }
+        ExecuteMsg::ExecuteSwapOperations {
+            offer_asset,
+            operations,
+            minimum_receive,
+            to,
+            pair_id,
+        } => {
+            if load_pause_status(deps.storage)?.swaps {
+                return Err(ContractError::SwapsPaused {});
+            }
+            execute_swap_operations(
+                deps,
+                env,
+                info,
+                offer_asset,
+                operations,
+                minimum_receive,
+                to,
+                pair_id,
             )
         }
         ExecuteMsg::RewardClaim {
             receiver,
             withdrawal_amount,
-        } => claim_investment_reward(deps, env, info, receiver, withdrawal_amount),
+            pay_fees_in_fury,
+        } => {
+            if load_pause_status(deps.storage)?.claims {
+                return Err(ContractError::ClaimsPaused {});
+            }
+            claim_investment_reward(
+                deps,
+                env,
+                info,
+                receiver,
+                withdrawal_amount,
+                pay_fees_in_fury.unwrap_or(false),
+            )
+        }
+        ExecuteMsg::ClaimAllMatured { pay_fees_in_fury } => {
+            if load_pause_status(deps.storage)?.claims {
+                return Err(ContractError::ClaimsPaused {});
+            }
+            claim_vested_bonds(deps, env, info, None, pay_fees_in_fury.unwrap_or(false))
+        }
+        ExecuteMsg::ClaimBond {
+            bond_id,
+            pay_fees_in_fury,
+        } => {
+            if load_pause_status(deps.storage)?.claims {
+                return Err(ContractError::ClaimsPaused {});
+            }
+            claim_vested_bonds(
+                deps,
+                env,
+                info,
+                Some(bond_id),
+                pay_fees_in_fury.unwrap_or(false),
+            )
+        }
+        ExecuteMsg::ClaimReferralRewards {} => claim_referral_rewards(deps, info),
+        ExecuteMsg::HarvestProtocolLiquidity {} => harvest_protocol_liquidity(deps, env, info),
+        ExecuteMsg::UnstakeProtocolLiquidity { lp_token, amount } => {
+            unstake_protocol_liquidity(deps, env, info, lp_token, amount)
+        }
+    }
+}
+
+/// Returns the proxy actions currently paused, nothing is paused until the status is first set
+fn load_pause_status(storage: &dyn Storage) -> StdResult<PauseStatus> {
+    Ok(PAUSE_STATUS.may_load(storage)?.unwrap_or_default())
+}
+
+/// Pauses or resumes proxy actions. The guardian can only pause actions so that a compromised
+/// guardian key cannot reopen them, resuming is left to the admin
+fn set_pause_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    swaps: Option<bool>,
+    pair_bonding: Option<bool>,
+    native_bonding: Option<bool>,
+    claims: Option<bool>,
+    withdrawals: Option<bool>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let is_admin = info.sender == config.admin_address;
+    let is_guardian = config.guardian_address.as_ref() == Some(&info.sender);
+    if !is_admin && !is_guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+    let updates = [swaps, pair_bonding, native_bonding, claims, withdrawals];
+    if !is_admin && updates.contains(&Some(false)) {
+        return Err(ContractError::Unauthorized {});
     }
+
+    let mut status = load_pause_status(deps.storage)?;
+    if let Some(swaps) = swaps {
+        status.swaps = swaps;
+    }
+    if let Some(pair_bonding) = pair_bonding {
+        status.pair_bonding = pair_bonding;
+    }
+    if let Some(native_bonding) = native_bonding {
+        status.native_bonding = native_bonding;
+    }
+    if let Some(claims) = claims {
+        status.claims = claims;
+    }
+    if let Some(withdrawals) = withdrawals {
+        status.withdrawals = withdrawals;
+    }
+    PAUSE_STATUS.save(deps.storage, &status)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_pause_status")
+        .add_attribute("swaps", status.swaps.to_string())
+        .add_attribute("pair_bonding", status.pair_bonding.to_string())
+        .add_attribute("native_bonding", status.native_bonding.to_string())
+        .add_attribute("claims", status.claims.to_string())
+        .add_attribute("withdrawals", status.withdrawals.to_string()))
 }
 
 fn configure_proxy(
@@ -291,466 +775,3208 @@ fn configure_proxy(
     Ok(Response::default())
 }
 
-fn process_received_message(
+fn update_config(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    received_message: Cw20ReceiveMsg,
+    msg: UpdateConfigMsg,
 ) -> Result<Response, ContractError> {
-    // let config: Config = CONFIG.load(deps.storage)?;
-    // let sender_addr = info.sender.clone();
-    // let contract_address = env.clone().contract.address;
-    // let balances = deps.querier.query_all_balances(contract_address.clone().into_string())?;
-    // let sender_balances = deps.querier.query_all_balances(sender_addr.clone().into_string())?;
-    // if true {
-    //     return Err(ContractError::Std(StdError::generic_err(format!(
-    //         "in process_received_message!!! with funds = {:?} and contract balances = {:?} for address {:?} and sender_balance = {:?} for sender = {:?}",
-    //         info.funds, balances, contract_address, sender_balances, sender_addr,
-    //     ))));
-    // }
-    match from_binary(&received_message.msg) {
-        // Ok(ProxyCw20HookMsg::Swap {
-        //     belief_price,
-        //     max_spread,
-        //     to,
-        // }) => {
-        //     let to_address: Option<String>;
-        //     match to {
-        //         Some(to_addr) => to_address = Some(to_addr),
-        //         None => to_address = Some(received_message.sender),
-        //     }
-        //     let swap_msg_to_send = ProxyCw20HookMsg::Swap {
-        //         belief_price: belief_price,
-        //         max_spread: max_spread,
-        //         to: to_address,
-        //     };
-        //     forward_swap_to_astro(deps, info, swap_msg_to_send, received_message.amount)
-        // }
-        Ok(ProxyCw20HookMsg::WithdrawLiquidity {}) => {
-            withdraw_liquidity(deps, env, info, received_message)
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(pair_discount_rate) = msg.pair_discount_rate {
+        config.pair_discount_rate = pair_discount_rate;
+    }
+    if let Some(pair_bonding_period_in_sec) = msg.pair_bonding_period_in_sec {
+        config.pair_bonding_period_in_sec = pair_bonding_period_in_sec;
+    }
+    if let Some(pair_fury_reward_wallet) = msg.pair_fury_reward_wallet {
+        config.pair_fury_reward_wallet =
+            addr_validate_to_lower(deps.api, &pair_fury_reward_wallet)?;
+    }
+    if let Some(pair_lp_tokens_holder) = msg.pair_lp_tokens_holder {
+        config.pair_lp_tokens_holder = addr_validate_to_lower(deps.api, &pair_lp_tokens_holder)?;
+    }
+    if let Some(pair_vesting_schedule) = msg.pair_vesting_schedule {
+        config.pair_vesting_schedule = pair_vesting_schedule;
+    }
+    if let Some(native_discount_rate) = msg.native_discount_rate {
+        config.native_discount_rate = native_discount_rate;
+    }
+    if let Some(native_bonding_period_in_sec) = msg.native_bonding_period_in_sec {
+        config.native_bonding_period_in_sec = native_bonding_period_in_sec;
+    }
+    if let Some(native_investment_reward_wallet) = msg.native_investment_reward_wallet {
+        config.native_investment_reward_wallet =
+            addr_validate_to_lower(deps.api, &native_investment_reward_wallet)?;
+    }
+    if let Some(native_investment_receive_wallet) = msg.native_investment_receive_wallet {
+        config.native_investment_receive_wallet =
+            addr_validate_to_lower(deps.api, &native_investment_receive_wallet)?;
+    }
+    if let Some(native_vesting_schedule) = msg.native_vesting_schedule {
+        config.native_vesting_schedule = native_vesting_schedule;
+    }
+    if let Some(authorized_liquidity_provider) = msg.authorized_liquidity_provider {
+        config.authorized_liquidity_provider =
+            addr_validate_to_lower(deps.api, &authorized_liquidity_provider)?;
+    }
+    if let Some(platform_fees_collector_wallet) = msg.platform_fees_collector_wallet {
+        config.platform_fees_collector_wallet =
+            addr_validate_to_lower(deps.api, &platform_fees_collector_wallet)?;
+    }
+    if let Some(platform_fees) = msg.platform_fees {
+        config.platform_fees = platform_fees;
+    }
+    if let Some(transaction_fees) = msg.transaction_fees {
+        config.transaction_fees = transaction_fees;
+    }
+    if let Some(swap_fees) = msg.swap_fees {
+        config.swap_fees = swap_fees;
+    }
+    if let Some(max_bonding_limit_per_user) = msg.max_bonding_limit_per_user {
+        config.max_bonding_limit_per_user = max_bonding_limit_per_user;
+    }
+    if let Some(oracle_address) = msg.oracle_address {
+        config.oracle_address = Some(addr_validate_to_lower(deps.api, &oracle_address)?);
+    }
+    if let Some(max_twap_deviation) = msg.max_twap_deviation {
+        config.max_twap_deviation = Some(max_twap_deviation);
+    }
+    if msg.disable_twap_pricing == Some(true) {
+        config.oracle_address = None;
+    }
+    if let Some(pair_bond_capacity) = msg.pair_bond_capacity {
+        config.pair_bond_capacity = pair_bond_capacity;
+    }
+    if let Some(native_bond_capacity) = msg.native_bond_capacity {
+        config.native_bond_capacity = native_bond_capacity;
+    }
+    if let Some(bonds_transferable) = msg.bonds_transferable {
+        config.bonds_transferable = bonds_transferable;
+    }
+    if let Some(early_unbond_penalty_rate) = msg.early_unbond_penalty_rate {
+        config.early_unbond_penalty_rate = Some(early_unbond_penalty_rate);
+    }
+    if msg.disable_early_unbond == Some(true) {
+        config.early_unbond_penalty_rate = None;
+    }
+    if let Some(early_unbond_penalty_destination) = msg.early_unbond_penalty_destination {
+        config.early_unbond_penalty_destination =
+            validate_penalty_destination(deps.as_ref(), early_unbond_penalty_destination)?;
+    }
+    if let Some(fury_fee_discount) = msg.fury_fee_discount {
+        config.fury_fee_discount = fury_fee_discount;
+    }
+    if let Some(fee_collectors) = msg.fee_collectors {
+        config.fee_collectors = validate_fee_collectors(deps.as_ref(), fee_collectors)?;
+    }
+    if let Some(maker_address) = msg.maker_address {
+        config.maker_address = Some(addr_validate_to_lower(deps.api, &maker_address)?);
+    }
+    if let Some(router_address) = msg.router_address {
+        config.router_address = Some(addr_validate_to_lower(deps.api, &router_address)?);
+    }
+    if let Some(guardian_address) = msg.guardian_address {
+        config.guardian_address = Some(addr_validate_to_lower(deps.api, &guardian_address)?);
+    }
+    if msg.remove_guardian.unwrap_or(false) {
+        config.guardian_address = None;
+    }
+    if let Some(referral_fee_share) = msg.referral_fee_share {
+        config.referral_fee_share = referral_fee_share;
+    }
+    if let Some(early_access_window_in_sec) = msg.early_access_window_in_sec {
+        config.early_access_window_in_sec = early_access_window_in_sec;
+    }
+    if let Some(early_access_merkle_root) = msg.early_access_merkle_root {
+        config.early_access_merkle_root = Some(early_access_merkle_root);
+    }
+    if msg.remove_early_access_merkle_root.unwrap_or(false) {
+        config.early_access_merkle_root = None;
+    }
+    if let Some(swap_limits) = msg.swap_limits {
+        config.swap_limits = swap_limits;
+    }
+    let mut generator_address = config.generator_address.clone();
+    if let Some(new_generator_address) = msg.generator_address {
+        generator_address = Some(addr_validate_to_lower(deps.api, &new_generator_address)?);
+    }
+    if msg.remove_generator.unwrap_or(false) {
+        generator_address = None;
+    }
+    if generator_address != config.generator_address {
+        // Staked LP tokens can only be withdrawn from the generator they were deposited in
+        if PROTOCOL_LIQUIDITY
+            .range(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+        {
+            return Err(ContractError::ProtocolLiquidityStaked {});
         }
-        Err(err) => Err(ContractError::Std(err)),
+        config.generator_address = generator_address;
     }
-    // Ok(Response::default())
-}
 
-pub fn incr_allow_for_provide_liquidity(
-    deps: DepsMut,
-    env: Env,
-    assets: [Asset; 2],
-    slippage_tolerance: Option<Decimal>,
-    auto_stake: Option<bool>,
-    receiver: Option<String>,
-    funds: Vec<Coin>,
-    user_address: String,
-    is_fury_provided: bool,
-) -> Result<Response, ContractError> {
-    let mut resp = Response::new();
-    let config: Config = CONFIG.load(deps.storage)?;
+    validate_config(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
 
-    // Get the amount of Fury tokens to be specified in transfer_from and increase_allowance
-    let mut amount = Uint128::zero();
-    if !assets[0].info.is_native_token() {
-        amount = assets[0].amount;
-    } else if !assets[1].info.is_native_token() {
-        amount = assets[1].amount;
+/// Checks that discount rates, fees and vesting schedules of the configuration are consistent
+fn validate_config(config: &Config) -> Result<(), ContractError> {
+    if config.pair_discount_rate as u128 >= HUNDRED_PERCENT
+        || config.native_discount_rate as u128 >= HUNDRED_PERCENT
+    {
+        return Err(ContractError::InvalidDiscountRate {});
     }
-
-    // Prepare submessage for Increase Allowance
-    let increase_allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
-        spender: config.pool_pair_address,
-        amount: amount,
-        expires: None,
-    };
-    let exec_incr_allow = WasmMsg::Execute {
-        contract_addr: config.custom_token_address.to_string(),
-        msg: to_binary(&increase_allowance_msg).unwrap(),
-        funds: vec![],
-    };
-    let mut send_incr_allow: SubMsg = SubMsg::new(exec_incr_allow);
-    let mut sub_req_id = 1;
-    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
-        req_id += 1;
-        SUB_REQ_ID.save(deps.storage, &req_id)?;
-        sub_req_id = req_id;
-    } else {
-        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    let total_fees = config
+        .platform_fees
+        .checked_add(config.transaction_fees)
+        .and_then(|fees| fees.checked_add(config.swap_fees))
+        .map_err(|_| ContractError::InvalidFees {})?;
+    if total_fees >= Uint128::from(HUNDRED_PERCENT) {
+        return Err(ContractError::InvalidFees {});
     }
-    send_incr_allow.reply_on = ReplyOn::Always;
-    send_incr_allow.id = sub_req_id;
+    validate_vesting_schedule(
+        &config.pair_vesting_schedule,
+        config.pair_bonding_period_in_sec,
+    )?;
+    validate_vesting_schedule(
+        &config.native_vesting_schedule,
+        config.native_bonding_period_in_sec,
+    )?;
+    for bond_capacity in [&config.pair_bond_capacity, &config.native_bond_capacity] {
+        if bond_capacity.max_fury_per_epoch.is_some() && bond_capacity.epoch_length_in_sec == 0 {
+            return Err(ContractError::InvalidBondCapacity {});
+        }
+    }
+    if let Some(early_unbond_penalty_rate) = config.early_unbond_penalty_rate {
+        if early_unbond_penalty_rate as u128 > HUNDRED_PERCENT {
+            return Err(ContractError::InvalidPenaltyRate {});
+        }
+    }
+    if config.fury_fee_discount as u128 > HUNDRED_PERCENT {
+        return Err(ContractError::InvalidFeeDiscount {});
+    }
+    if config.referral_fee_share as u128 > HUNDRED_PERCENT {
+        return Err(ContractError::InvalidReferralFeeShare {});
+    }
+    if config.swap_limits.max_fury_per_window.is_some() && config.swap_limits.window_in_sec == 0 {
+        return Err(ContractError::InvalidSwapLimits {});
+    }
+    if let Some(max_offer_reserve_ratio) = config.swap_limits.max_offer_reserve_ratio {
+        if max_offer_reserve_ratio > Decimal::one() {
+            return Err(ContractError::InvalidSwapLimits {});
+        }
+    }
+    if let Some(merkle_root) = &config.early_access_merkle_root {
+        match hex::decode(merkle_root) {
+            Ok(root) if root.len() == 32 => {}
+            _ => return Err(ContractError::InvalidMerkleRoot {}),
+        }
+    }
+    if !config.fee_collectors.is_empty() {
+        let total_share: u128 = config
+            .fee_collectors
+            .iter()
+            .map(|collector| collector.share as u128)
+            .sum();
+        let maker_without_address = config.maker_address.is_none()
+            && config
+                .fee_collectors
+                .iter()
+                .any(|collector| collector.recipient == FeeRecipient::Maker);
+        if total_share != HUNDRED_PERCENT || maker_without_address {
+            return Err(ContractError::InvalidFeeCollectors {});
+        }
+    }
+    Ok(())
+}
 
-    resp = resp.add_submessage(send_incr_allow);
+/// Validates the address of a penalty destination and stores it in lower case
+fn validate_penalty_destination(
+    deps: Deps,
+    destination: PenaltyDestination,
+) -> StdResult<PenaltyDestination> {
+    match destination {
+        PenaltyDestination::Address { address } => Ok(PenaltyDestination::Address {
+            address: addr_validate_to_lower(deps.api, &address)?.to_string(),
+        }),
+        destination => Ok(destination),
+    }
+}
 
-    let pl_msg = PairExecuteMsg::ProvideLiquidity {
-        assets: assets,
-        slippage_tolerance: slippage_tolerance,
-        auto_stake: auto_stake,
-        receiver: receiver,
-    };
+fn validate_fee_collectors(
+    deps: Deps,
+    fee_collectors: Vec<FeeCollector>,
+) -> StdResult<Vec<FeeCollector>> {
+    fee_collectors
+        .into_iter()
+        .map(|collector| match collector.recipient {
+            FeeRecipient::Address { address } => Ok(FeeCollector {
+                recipient: FeeRecipient::Address {
+                    address: addr_validate_to_lower(deps.api, &address)?.to_string(),
+                },
+                share: collector.share,
+            }),
+            FeeRecipient::Maker => Ok(collector),
+        })
+        .collect()
+}
 
-    // let data_msg = format!("{:?}", pl_msg).into_bytes();
+fn validate_vesting_schedule(
+    vesting_schedule: &VestingSchedule,
+    bonding_period_in_sec: u64,
+) -> Result<(), ContractError> {
+    if let VestingSchedule::CliffThenLinear {
+        cliff_period_in_sec,
+    } = vesting_schedule
+    {
+        if *cliff_period_in_sec >= bonding_period_in_sec {
+            return Err(ContractError::InvalidVestingSchedule {});
+        }
+    }
+    Ok(())
+}
 
-    // Save the submessage_payload
-    SUB_MESSAGE_DETAILS.save(
-        deps.storage,
-        sub_req_id.to_string(),
-        &SubMessageDetails {
-            sub_req_id: sub_req_id.to_string(),
-            request_type: SubMessageType::ProvideLiquiditySubMsg,
-            next_action: SubMessageNextAction::ProvideLiquidity,
-            sub_message_payload: to_binary(&pl_msg)?,
-            funds: funds,
-            user_address: user_address,
-            is_fury_provided: is_fury_provided,
+fn add_bond_term(
+    deps: DepsMut,
+    info: MessageInfo,
+    bond_type: BondType,
+    bonding_period_in_sec: u64,
+    discount_rate: u16,
+    vesting_schedule: Option<VestingSchedule>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if discount_rate as u128 >= HUNDRED_PERCENT {
+        return Err(ContractError::InvalidDiscountRate {});
+    }
+    if let Some(vesting_schedule) = &vesting_schedule {
+        validate_vesting_schedule(vesting_schedule, bonding_period_in_sec)?;
+    }
+
+    let term_id = BOND_TERM_ID.may_load(deps.storage)?.unwrap_or_default() + 1;
+    BOND_TERM_ID.save(deps.storage, &term_id)?;
+    BOND_TERMS.save(
+        deps.storage,
+        U64Key::new(term_id),
+        &BondTerm {
+            term_id,
+            bond_type,
+            bonding_period_in_sec,
+            discount_rate,
+            vesting_schedule,
+            active: true,
         },
     )?;
-
-    Ok(resp.add_attribute(
-        "action",
-        "Increase Allowance for proxy contract to Provide Liquidity",
-    ))
+    Ok(Response::new()
+        .add_attribute("action", "add_bond_term")
+        .add_attribute("term_id", term_id.to_string()))
 }
 
-pub fn forward_provide_liquidity_to_astro(
+fn retire_bond_term(
     deps: DepsMut,
-    env: Env,
-    assets: [Asset; 2],
-    slippage_tolerance: Option<Decimal>,
-    auto_stake: Option<bool>,
-    receiver: Option<String>,
-    funds: Vec<Coin>,
+    info: MessageInfo,
+    term_id: u64,
 ) -> Result<Response, ContractError> {
-    let config: Config = CONFIG.load(deps.storage)?;
-
-    let mut funds_to_pass: Vec<Coin> = Vec::new();
-    for fund in funds {
-        let asset = Asset {
-            amount: fund.amount,
-            info: AssetInfo::NativeToken {
-                denom: fund.denom.clone(),
-            },
-        };
-        let c = Coin {
-            denom: fund.denom,
-            amount: fund
-                .amount
-                .checked_sub(asset.compute_tax(&deps.querier)?)
-                .unwrap(),
-        };
-        funds_to_pass.push(c);
-    }
-
-    let pl_msg = PairExecuteMsg::ProvideLiquidity {
-        assets,
-        slippage_tolerance,
-        auto_stake,
-        receiver,
-    };
-    let exec = WasmMsg::Execute {
-        contract_addr: config.pool_pair_address.to_string(),
-        msg: to_binary(&pl_msg).unwrap(),
-        funds: funds_to_pass,
-    };
-    let mut send: SubMsg = SubMsg::new(exec);
-    let mut sub_req_id = 1;
-    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
-        req_id += 1;
-        SUB_REQ_ID.save(deps.storage, &req_id)?;
-        sub_req_id = req_id;
-    } else {
-        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
     }
-    send.id = sub_req_id;
-    send.reply_on = ReplyOn::Always;
-
-    let mut resp = Response::new();
-    resp = resp.add_submessage(send);
-    let data_msg = format!("provide liquidity details {:?}", pl_msg).into_bytes();
-    Ok(resp
-        .add_attribute("action", "Sending provide liquidity message")
-        .set_data(data_msg))
+    let mut term = BOND_TERMS
+        .may_load(deps.storage, U64Key::new(term_id))?
+        .ok_or(ContractError::BondTermNotFound { term_id })?;
+    term.active = false;
+    BOND_TERMS.save(deps.storage, U64Key::new(term_id), &term)?;
+    Ok(Response::new()
+        .add_attribute("action", "retire_bond_term")
+        .add_attribute("term_id", term_id.to_string()))
 }
 
-pub fn forward_swap_to_astro(
+/// Registers a Fury pool or replaces its settings
+fn set_pair(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    received_message: Cw20HookMsg,
-    amount: Uint128,
-    funds_to_send: Vec<Coin>,
-    platform_fees: Coin,
+    pair_id: String,
+    settings: PairSettings,
 ) -> Result<Response, ContractError> {
-    let config: Config = CONFIG.load(deps.storage)?;
-    let send_msg = Cw20ExecuteMsg::Send {
-        contract: config.pool_pair_address,
-        amount: amount,
-        msg: to_binary(&received_message)?,
-    };
-    let exec = WasmMsg::Execute {
-        contract_addr: config.custom_token_address.clone().into_string(),
-        msg: to_binary(&send_msg).unwrap(),
-        funds: funds_to_send,
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    settings.quote_asset.check(deps.api)?;
+    if is_fury(&config, &settings.quote_asset) {
+        return Err(ContractError::InvalidPairSettings {});
+    }
+    let oracle_address = match &settings.oracle_address {
+        Some(oracle_address) => Some(addr_validate_to_lower(deps.api, oracle_address)?.to_string()),
+        None => None,
     };
-    // let mut send: SubMsg = SubMsg::new(exec);
-    // let mut sub_req_id = 1;
-    // if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
-    //     req_id += 1;
-    //     SUB_REQ_ID.save(deps.storage, &req_id)?;
-    //     sub_req_id = req_id;
-    // } else {
-    //     SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
-    // }
-
-    // send.id = sub_req_id;
-    // send.reply_on = ReplyOn::Always;
-
-    let mut resp = Response::new();
-    // Add message to transfer fury tokens
-    resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: config.custom_token_address.into_string(),
-        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-            owner: info.sender.to_string(),
-            recipient: env.contract.address.to_string(),
-            amount: amount,
-        })?,
-        funds: vec![],
-    }));
-    // resp = resp.add_submessage(send);
-    resp = resp.add_message(CosmosMsg::Wasm(exec));
-    //Add bank message to transfer platform fees to platform fee collector wallet
-    let pf_asset = Asset {
-        info: AssetInfo::NativeToken {
-            denom: String::from("uusd"),
-        },
-        amount: platform_fees.amount,
+    let settings = PairSettings {
+        pool_pair_address: addr_validate_to_lower(deps.api, &settings.pool_pair_address)?
+            .to_string(),
+        liquidity_token: addr_validate_to_lower(deps.api, &settings.liquidity_token)?.to_string(),
+        oracle_address,
+        ..settings
     };
-    let taxed_platform_fees = pf_asset.deduct_tax(&deps.querier)?;
-    resp = resp.add_message(CosmosMsg::Bank(BankMsg::Send {
-        to_address: config.platform_fees_collector_wallet.into_string(),
-        amount: vec![taxed_platform_fees],
-    }));
-    Ok(resp.add_attribute("action", "Forwarding swap message to pool pair address"))
+    PAIRS.save(deps.storage, &pair_id, &settings)?;
+    validate_config(&load_config(deps.storage, Some(&pair_id))?)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_pair")
+        .add_attribute("pair_id", pair_id))
 }
 
-pub fn provide_native_liquidity(
+/// Removes a registered Fury pool once no pending request needs its settings to complete or roll back
+fn remove_pair(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    asset: Asset,
-    slippage_tolerance: Option<Decimal>,
-    auto_stake: Option<bool>,
+    pair_id: String,
 ) -> Result<Response, ContractError> {
-    //Check if assets provided are native tokens
-    asset.info.check(deps.api)?;
-    //First check if platform fees are provided
-    let required_ust_fees: Uint128;
-    required_ust_fees = query_platform_fees(
-        deps.as_ref(),
-        to_binary(&ExecuteMsg::ProvideNativeForReward {
-            asset: asset.clone(),
-            slippage_tolerance: slippage_tolerance.clone(),
-            auto_stake: auto_stake.clone(),
-        })?,
-    )?;
-    let mut fees = Uint128::zero();
-    for fund in info.funds.clone() {
-        if fund.denom == "uusd" {
-            fees = fees.checked_add(fund.amount).unwrap();
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if PAIRS.may_load(deps.storage, &pair_id)?.is_none() {
+        return Err(ContractError::PairNotFound { pair_id });
+    }
+    for item in PENDING_REQUESTS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, request) = item?;
+        if request.pair_id.as_deref() == Some(pair_id.as_str()) {
+            return Err(ContractError::PairInUse { pair_id });
         }
     }
-    let native_tax = asset.compute_tax(&deps.querier)?;
-    //Remove platform fees from funds
-    let funds_to_pass = fees.checked_sub(required_ust_fees).unwrap();
-    fees = fees.checked_sub(asset.amount).unwrap();
-    fees = fees.checked_sub(native_tax).unwrap();
-    if fees < required_ust_fees {
-        return Err(ContractError::InsufficientFees {
-            required: required_ust_fees,
-            received: fees,
-        });
+    PAIRS.remove(deps.storage, &pair_id);
+    Ok(Response::new()
+        .add_attribute("action", "remove_pair")
+        .add_attribute("pair_id", pair_id))
+}
+
+/// Checks the anti-whale limits on a swap of `offer_asset` by `address` and records the swap in
+/// the rolling window of the address. Fury bought is estimated from the pool price
+fn apply_swap_limits(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    address: &str,
+    offer_asset: &Asset,
+) -> Result<(), ContractError> {
+    let limits = &config.swap_limits;
+    if let Some(max_offer_reserve_ratio) = limits.max_offer_reserve_ratio {
+        let pool: PoolResponse = deps
+            .querier
+            .query_wasm_smart(config.pool_pair_address.clone(), &Pool {})?;
+        let reserve = pool
+            .assets
+            .iter()
+            .find(|asset| asset.info == offer_asset.info)
+            .map(|asset| asset.amount)
+            .unwrap_or_default();
+        let max_offer = reserve * max_offer_reserve_ratio;
+        if offer_asset.amount > max_offer {
+            return Err(ContractError::OfferExceedsReserveLimit {
+                offer: offer_asset.amount,
+                max_offer,
+            });
+        }
+    }
+    if limits.max_fury_per_window.is_none() && limits.cooldown_in_sec == 0 {
+        return Ok(());
     }
-    // Platform fees received is good, now proceed
-    //transfer it to platform_fees_collector_wallet
-    let mut funds_to_send = vec![Coin::new(funds_to_pass.u128(), String::from("uusd"))];
-    let platform_fees = Coin::new(required_ust_fees.u128(), format!("uusd"));
 
-    if let AssetInfo::NativeToken { denom, .. } = &asset.info {
-        funds_to_send = vec![Coin {
-            denom: denom.to_string(),
-            amount: asset.amount,
-        }];
+    let now = env.block.time.seconds();
+    let mut activity: SwapActivity = SWAP_ACTIVITY
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
+    if let Some(last_swap) = activity.swaps.last() {
+        let next_swap = last_swap.time + limits.cooldown_in_sec;
+        if now < next_swap {
+            return Err(ContractError::SwapCooldown { next_swap });
+        }
+    }
+    let fury_amount = if is_fury(config, &offer_asset.info) {
+        offer_asset.amount
+    } else {
+        get_fury_equivalent_to_ust(deps.as_ref(), config, offer_asset.amount)?
+    };
+    activity
+        .swaps
+        .retain(|swap| swap.time + limits.window_in_sec > now);
+    if let Some(max_fury_per_window) = limits.max_fury_per_window {
+        let swapped: Uint128 = activity.swaps.iter().map(|swap| swap.fury_amount).sum();
+        let remaining = max_fury_per_window.saturating_sub(swapped);
+        if fury_amount > remaining {
+            return Err(ContractError::SwapWindowLimitExceeded {
+                fury_amount,
+                remaining,
+            });
+        }
     }
+    activity.swaps.push(SwapRecord {
+        time: now,
+        fury_amount,
+    });
+    SWAP_ACTIVITY.save(deps.storage, address, &activity)?;
+    Ok(())
+}
 
-    let config = CONFIG.load(deps.storage)?;
-    let assets = [
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: asset.amount,
-        },
-        Asset {
-            info: AssetInfo::Token {
-                contract_addr: config.custom_token_address,
-            },
-            amount: Uint128::from(0u128),
-        },
-    ];
+/// Start of the early access window, None when early access is disabled
+fn early_access_window_start(config: &Config) -> Option<Timestamp> {
+    if config.early_access_window_in_sec == 0 {
+        return None;
+    }
+    Some(Timestamp::from_seconds(
+        config
+            .swap_opening_date
+            .seconds()
+            .saturating_sub(config.early_access_window_in_sec),
+    ))
+}
 
-    let receiver: Option<String>;
-    receiver = Some(config.native_investment_receive_wallet.to_string());
+/// Uses the early access allowance of an address swapping before the opening date, `amount` is
+/// the quote asset value of the swap. Bonding before the opening date stays open to everyone
+fn use_early_access(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    address: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let window_start = early_access_window_start(config).unwrap_or(config.swap_opening_date);
+    if env.block.time < window_start {
+        return Err(ContractError::EarlyAccessNotOpen {
+            window_start: window_start.seconds(),
+        });
+    }
+    let mut allowance = EARLY_ACCESS_ALLOWANCES
+        .may_load(storage, address.as_str())?
+        .ok_or_else(|| ContractError::NotEligibleForEarlyAccess {
+            address: address.to_string(),
+        })?;
+    let remaining = allowance.cap.saturating_sub(allowance.used);
+    if amount > remaining {
+        return Err(ContractError::EarlyAccessCapExceeded {
+            required: amount,
+            remaining,
+        });
+    }
+    allowance.used += amount;
+    EARLY_ACCESS_ALLOWANCES.save(storage, address.as_str(), &allowance)?;
+    Ok(())
+}
 
-    let user_address = info.sender.into_string();
-    transfer_custom_assets_from_funds_owner_to_proxy(
-        deps,
-        env,
-        assets,
-        slippage_tolerance,
-        auto_stake,
-        receiver,
-        funds_to_send,
-        user_address,
-        NO_FURY_PROVIDED,
-        Some(platform_fees),
-    )
+/// Checks that the address and cap are a leaf of the early access Merkle tree. Leaves are the
+/// SHA-256 of `{address},{cap}`, the separator keeping an address ending in digits from being
+/// read with a different cap. Pairs of hashes are sorted before being hashed
+fn verify_early_access_proof(
+    config: &Config,
+    address: &Addr,
+    cap: Uint128,
+    proof: &[String],
+) -> Result<(), ContractError> {
+    let merkle_root = config
+        .early_access_merkle_root
+        .as_ref()
+        .ok_or(ContractError::InvalidMerkleProof {})?;
+    let mut hash = Sha256::digest(format!("{},{}", address, cap).as_bytes()).to_vec();
+    for sibling in proof {
+        let sibling = hex::decode(sibling).map_err(|_| ContractError::InvalidMerkleProof {})?;
+        let pair = if hash <= sibling {
+            [hash, sibling].concat()
+        } else {
+            [sibling, hash].concat()
+        };
+        hash = Sha256::digest(&pair).to_vec();
+    }
+    if hex::decode(merkle_root).map_err(|_| ContractError::InvalidMerkleRoot {})? != hash {
+        return Err(ContractError::InvalidMerkleProof {});
+    }
+    Ok(())
 }
 
-pub fn transfer_native_assets_to_native_investment_receive_wallet(
+/// Grants or revokes early access, the usage of addresses already granted is kept
+fn update_early_access_allowlist(
     deps: DepsMut,
-    receiver: Option<String>,
-    funds: Vec<Coin>,
+    info: MessageInfo,
+    add: Vec<EarlyAccessEntry>,
+    remove: Vec<String>,
 ) -> Result<Response, ContractError> {
-    let mut funds_to_pass: Vec<Coin> = Vec::new();
-    for fund in funds {
-        let asset = Asset {
-            amount: fund.amount,
-            info: AssetInfo::NativeToken {
-                denom: fund.denom.clone(),
-            },
-        };
-        let c = Coin {
-            denom: fund.denom,
-            amount: fund
-                .amount
-                .checked_sub(asset.compute_tax(&deps.querier)?)
-                .unwrap(),
-        };
-        funds_to_pass.push(c);
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
     }
-
-    let resp = Response::new();
-    Ok(resp.add_message(CosmosMsg::Bank(BankMsg::Send {
-        to_address: receiver.unwrap(),
-        amount: funds_to_pass,
-    })))
+    for entry in add {
+        let address = addr_validate_to_lower(deps.api, &entry.address)?;
+        let mut allowance = EARLY_ACCESS_ALLOWANCES
+            .may_load(deps.storage, address.as_str())?
+            .unwrap_or_default();
+        allowance.cap = entry.cap;
+        EARLY_ACCESS_ALLOWANCES.save(deps.storage, address.as_str(), &allowance)?;
+    }
+    for address in remove {
+        let address = addr_validate_to_lower(deps.api, &address)?;
+        EARLY_ACCESS_ALLOWANCES.remove(deps.storage, address.as_str());
+    }
+    Ok(Response::new().add_attribute("action", "update_early_access_allowlist"))
 }
 
-pub fn transfer_custom_assets_from_funds_owner_to_proxy(
+/// Grants early access to the sender from its leaf in the early access Merkle tree. An address
+/// also on the allowlist keeps the larger cap
+fn register_early_access(
     deps: DepsMut,
-    env: Env,
-    assets: [Asset; 2],
-    slippage_tolerance: Option<Decimal>,
-    auto_stake: Option<bool>,
-    receiver: Option<String>,
-    funds: Vec<Coin>,
-    user_address: String,
-    is_fury_provided: bool,
-    platform_fees: Option<Coin>,
+    info: MessageInfo,
+    cap: Uint128,
+    proof: Vec<String>,
 ) -> Result<Response, ContractError> {
-    let mut fury_amount_provided = Uint128::zero();
-    let mut ust_amount_provided = Uint128::zero();
-    if is_fury_provided {
-        if !assets[0].info.is_native_token() {
+    let config = CONFIG.load(deps.storage)?;
+    verify_early_access_proof(&config, &info.sender, cap, &proof)?;
+    let mut allowance: EarlyAccessAllowance = EARLY_ACCESS_ALLOWANCES
+        .may_load(deps.storage, info.sender.as_str())?
+        .unwrap_or_default();
+    allowance.cap = allowance.cap.max(cap);
+    EARLY_ACCESS_ALLOWANCES.save(deps.storage, info.sender.as_str(), &allowance)?;
+    Ok(Response::new()
+        .add_attribute("action", "register_early_access")
+        .add_attribute("cap", allowance.cap.to_string()))
+}
+
+/// Loads the configuration of the pair a pending request is made on
+fn load_request_config(storage: &dyn Storage, request_id: u64) -> StdResult<Config> {
+    let request = PENDING_REQUESTS.load(storage, U64Key::new(request_id))?;
+    load_config(storage, request.pair_id.as_deref())
+}
+
+/// Loads the configuration of the pair an execute message is made on, the configured pool is
+/// used for messages that do not name a pair
+fn load_message_config(storage: &dyn Storage, msg: &Binary) -> StdResult<Config> {
+    let pair_id = match from_binary(msg) {
+        Ok(ExecuteMsg::ProvidePairForReward { pair_id, .. })
+        | Ok(ExecuteMsg::ProvideNativeForReward { pair_id, .. })
+        | Ok(ExecuteMsg::ProvideLiquidity { pair_id, .. })
+        | Ok(ExecuteMsg::Swap { pair_id, .. })
+        | Ok(ExecuteMsg::ExecuteSwapOperations { pair_id, .. }) => pair_id,
+        _ => None,
+    };
+    load_config(storage, pair_id.as_deref())
+}
+
+/// Loads a bond term that can be picked for a new bond of the given type
+fn load_active_bond_term(
+    storage: &dyn Storage,
+    term_id: u64,
+    bond_type: BondType,
+) -> Result<BondTerm, ContractError> {
+    let term = BOND_TERMS
+        .may_load(storage, U64Key::new(term_id))?
+        .ok_or(ContractError::BondTermNotFound { term_id })?;
+    if !term.active || term.bond_type != bond_type {
+        return Err(ContractError::BondTermUnavailable { term_id });
+    }
+    Ok(term)
+}
+
+fn transfer_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let recipient_addr = addr_validate_to_lower(deps.api, &recipient)?;
+    transfer_bond(
+        deps,
+        &env,
+        info.sender.as_str(),
+        recipient_addr.to_string(),
+        &token_id,
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "transfer_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", recipient_addr)
+        .add_attribute("token_id", token_id))
+}
+
+fn send_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let contract_addr = addr_validate_to_lower(deps.api, &contract)?;
+    transfer_bond(
+        deps,
+        &env,
+        info.sender.as_str(),
+        contract_addr.to_string(),
+        &token_id,
+    )?;
+    let receive_msg = Cw721ReceiveMsg {
+        sender: info.sender.to_string(),
+        token_id: token_id.clone(),
+        msg,
+    };
+    Ok(Response::new()
+        .add_message(receive_msg.into_cosmos_msg(contract_addr.to_string())?)
+        .add_attribute("action", "send_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", contract_addr)
+        .add_attribute("token_id", token_id))
+}
+
+/// Moves a bond to the recipient on behalf of its owner, an operator of the owner or an approved
+/// spender. Claims of the bond then pay the recipient
+fn transfer_bond(
+    deps: DepsMut,
+    env: &Env,
+    sender: &str,
+    recipient: String,
+    token_id: &str,
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.bonds_transferable {
+        return Err(ContractError::BondTransfersDisabled {});
+    }
+    let bond_id = parse_token_id(token_id)?;
+    let owner = load_bond_owner(deps.storage, bond_id)?;
+    if sender != owner
+        && !is_operator(deps.storage, env, &owner, sender)?
+        && !BOND_APPROVALS
+            .may_load(deps.storage, U64Key::new(bond_id))?
+            .unwrap_or_default()
+            .iter()
+            .any(|approval| approval.spender == sender && !approval.expires.is_expired(&env.block))
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut bond = remove_bond(deps.storage, owner, bond_id)?
+        .ok_or(ContractError::BondNotFound { bond_id })?;
+
+    let recipient_bonds = read_user_bonds(deps.storage, &recipient)?;
+    if config.max_bonding_limit_per_user <= recipient_bonds.len() as u64 {
+        return Err(ContractError::BondLimitReached {
+            user_address: recipient,
+        });
+    }
+    bond.user_address = recipient.clone();
+    BONDED_REWARDS_DETAILS.save(deps.storage, (&recipient, U64Key::new(bond_id)), &bond)?;
+    BOND_OWNERS.save(deps.storage, U64Key::new(bond_id), &recipient)?;
+    Ok(())
+}
+
+/// Allows the spender to transfer a bond until the approval expires, only the owner of the bond
+/// and its operators can approve
+fn approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let bond_id = parse_token_id(&token_id)?;
+    check_can_approve(deps.as_ref(), &env, &info, bond_id)?;
+    let spender_addr = addr_validate_to_lower(deps.api, &spender)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::ApprovalExpired {});
+    }
+    BOND_APPROVALS.update(
+        deps.storage,
+        U64Key::new(bond_id),
+        |approvals| -> StdResult<_> {
+            let mut approvals = approvals.unwrap_or_default();
+            approvals.retain(|approval| approval.spender != spender_addr.as_str());
+            approvals.push(Approval {
+                spender: spender_addr.to_string(),
+                expires,
+            });
+            Ok(approvals)
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "approve")
+        .add_attribute("sender", info.sender)
+        .add_attribute("spender", spender_addr)
+        .add_attribute("token_id", token_id))
+}
+
+/// Removes the approval of the spender on a bond
+fn revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let bond_id = parse_token_id(&token_id)?;
+    check_can_approve(deps.as_ref(), &env, &info, bond_id)?;
+    let spender_addr = addr_validate_to_lower(deps.api, &spender)?;
+    let mut approvals = BOND_APPROVALS
+        .may_load(deps.storage, U64Key::new(bond_id))?
+        .unwrap_or_default();
+    approvals.retain(|approval| approval.spender != spender_addr.as_str());
+    if approvals.is_empty() {
+        BOND_APPROVALS.remove(deps.storage, U64Key::new(bond_id));
+    } else {
+        BOND_APPROVALS.save(deps.storage, U64Key::new(bond_id), &approvals)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "revoke")
+        .add_attribute("sender", info.sender)
+        .add_attribute("spender", spender_addr)
+        .add_attribute("token_id", token_id))
+}
+
+/// Allows the operator to transfer and approve all bonds of the sender until the approval expires
+fn approve_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let operator_addr = addr_validate_to_lower(deps.api, &operator)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::ApprovalExpired {});
+    }
+    BOND_OPERATORS.save(
+        deps.storage,
+        (info.sender.as_str(), operator_addr.as_str()),
+        &expires,
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "approve_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("operator", operator_addr))
+}
+
+/// Removes the approval of the operator on the bonds of the sender
+fn revoke_all(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator_addr = addr_validate_to_lower(deps.api, &operator)?;
+    BOND_OPERATORS.remove(deps.storage, (info.sender.as_str(), operator_addr.as_str()));
+    Ok(Response::new()
+        .add_attribute("action", "revoke_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("operator", operator_addr))
+}
+
+/// Checks that the sender owns the bond or is an operator of its owner
+fn check_can_approve(
+    deps: Deps,
+    env: &Env,
+    info: &MessageInfo,
+    bond_id: u64,
+) -> Result<(), ContractError> {
+    let owner = load_bond_owner(deps.storage, bond_id)?;
+    if info.sender != owner && !is_operator(deps.storage, env, &owner, info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// Returns whether the operator holds an unexpired approval on all bonds of the owner
+fn is_operator(storage: &dyn Storage, env: &Env, owner: &str, operator: &str) -> StdResult<bool> {
+    Ok(BOND_OPERATORS
+        .may_load(storage, (owner, operator))?
+        .map_or(false, |expires| !expires.is_expired(&env.block)))
+}
+
+fn load_bond_owner(storage: &dyn Storage, bond_id: u64) -> Result<String, ContractError> {
+    BOND_OWNERS
+        .may_load(storage, U64Key::new(bond_id))?
+        .ok_or(ContractError::BondNotFound { bond_id })
+}
+
+/// Converts a token id into the id of the bond it represents, bond ids start at 1
+fn parse_token_id(token_id: &str) -> Result<u64, ContractError> {
+    match token_id.parse::<u64>() {
+        Ok(bond_id) if bond_id > 0 => Ok(bond_id),
+        _ => Err(ContractError::InvalidTokenId {
+            token_id: token_id.to_string(),
+        }),
+    }
+}
+
+fn process_received_message(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received_message: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // let config: Config = CONFIG.load(deps.storage)?;
+    // let sender_addr = info.sender.clone();
+    // let contract_address = env.clone().contract.address;
+    // let balances = deps.querier.query_all_balances(contract_address.clone().into_string())?;
+    // let sender_balances = deps.querier.query_all_balances(sender_addr.clone().into_string())?;
+    // if true {
+    //     return Err(ContractError::Std(StdError::generic_err(format!(
+    //         "in process_received_message!!! with funds = {:?} and contract balances = {:?} for address {:?} and sender_balance = {:?} for sender = {:?}",
+    //         info.funds, balances, contract_address, sender_balances, sender_addr,
+    //     ))));
+    // }
+    match from_binary(&received_message.msg) {
+        Ok(ProxyCw20HookMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            pair_id,
+        }) => provide_received_liquidity(
+            deps,
+            env,
+            info,
+            received_message,
+            assets,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            pair_id,
+        ),
+        Ok(ProxyCw20HookMsg::Swap {
+            belief_price,
+            max_spread,
+            to,
+            pair_id,
+        }) => {
+            if load_pause_status(deps.storage)?.swaps {
+                return Err(ContractError::SwapsPaused {});
+            }
+            swap_received_fury(
+                deps,
+                env,
+                info,
+                received_message,
+                belief_price,
+                max_spread,
+                to,
+                pair_id,
+            )
+        }
+        Ok(ProxyCw20HookMsg::WithdrawLiquidity {
+            min_assets,
+            receiver,
+        }) => {
+            if load_pause_status(deps.storage)?.withdrawals {
+                return Err(ContractError::WithdrawalsPaused {});
+            }
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                received_message,
+                min_assets.unwrap_or_default(),
+                receiver,
+            )
+        }
+        Err(err) => Err(ContractError::Std(err)),
+    }
+    // Ok(Response::default())
+}
+
+/// Provides the Fury sent by the authorized liquidity provider as pair liquidity. The proxy
+/// already holds the Fury, so the request starts at the allowance of the pair on it and the
+/// CW20 quote token is pulled from the provider when liquidity is provided
+#[allow(clippy::too_many_arguments)]
+fn provide_received_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received_message: Cw20ReceiveMsg,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    pair_id: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    if info.sender != config.custom_token_address
+        || received_message.sender != config.authorized_liquidity_provider
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let AssetInfo::NativeToken { .. } = config.quote_asset {
+        return Err(ContractError::NativeQuoteAssetNotSupported {});
+    }
+    let fury_amount = fury_amount(&config, &assets);
+    if fury_amount != received_message.amount {
+        return Err(ContractError::FuryAmountMismatch {
+            required: fury_amount,
+            received: received_message.amount,
+        });
+    }
+    let receiver = match receiver {
+        Some(receiver) => addr_validate_to_lower(deps.api, &receiver)?.to_string(),
+        None => received_message.sender.clone(),
+    };
+
+    let request_id = create_pending_request(
+        deps.storage,
+        received_message.sender.clone(),
+        vec![],
+        fury_amount,
+        pair_id,
+        None,
+    )?;
+    incr_allow_for_provide_liquidity(
+        deps,
+        env,
+        assets,
+        slippage_tolerance,
+        auto_stake,
+        Some(receiver),
+        vec![],
+        received_message.sender,
+        FURY_PROVIDED,
+        request_id,
+    )
+}
+
+/// Sells Fury sent through the CW20 hook in a single transaction. No funds can be attached to
+/// a CW20 send, so the platform fees are taken out of the proceeds of the sale as with
+/// deduct_fees_from_proceeds, and belief_price and max_spread apply to the net proceeds
+#[allow(clippy::too_many_arguments)]
+fn swap_received_fury(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received_message: Cw20ReceiveMsg,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<String>,
+    pair_id: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    if info.sender != config.custom_token_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if config.swap_opening_date.nanos() > env.block.time.nanos() {
+        if config.early_access_window_in_sec == 0 {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Swap is not enabled yet!!!",
+            ))));
+        }
+        let fury_value =
+            get_ust_equivalent_to_fury(deps.as_ref(), &config, received_message.amount)?;
+        let sender = addr_validate_to_lower(deps.api, &received_message.sender)?;
+        use_early_access(deps.storage, &env, &config, &sender, fury_value)?;
+    }
+    apply_swap_limits(
+        deps.branch(),
+        &env,
+        &config,
+        &received_message.sender,
+        &Asset {
+            info: fury_asset_info(&config),
+            amount: received_message.amount,
+        },
+    )?;
+    let to_address = match to {
+        Some(to_addr) => addr_validate_to_lower(deps.api, &to_addr)?.to_string(),
+        None => received_message.sender.clone(),
+    };
+    let offer_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: config.custom_token_address.clone(),
+        },
+        amount: received_message.amount,
+    };
+    let required_ust_fees = query_platform_fees(
+        deps.as_ref(),
+        env.clone(),
+        to_binary(&ExecuteMsg::Swap {
+            offer_asset: offer_asset.clone(),
+            belief_price,
+            max_spread,
+            to: Some(to_address.clone()),
+            pay_fees_in_fury: None,
+            deduct_fees_from_proceeds: Some(true),
+            pair_id: pair_id.clone(),
+            referrer: None,
+        })?,
+        Some(received_message.sender.clone()),
+    )?;
+    // The proxy already holds the sent Fury
+    swap_deducting_fees_from_proceeds(
+        deps,
+        env,
+        received_message.sender,
+        true,
+        offer_asset,
+        belief_price,
+        max_spread,
+        to_address,
+        required_ust_fees,
+        vec![],
+        pair_id,
+        None,
+    )
+}
+
+pub fn incr_allow_for_provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    funds: Vec<Coin>,
+    user_address: String,
+    is_fury_provided: bool,
+    request_id: u64,
+) -> Result<Response, ContractError> {
+    let mut resp = Response::new();
+    let config: Config = load_request_config(deps.storage, request_id)?;
+
+    // Get the amount of Fury tokens to be specified in transfer_from and increase_allowance
+    let mut amount = Uint128::zero();
+    if is_fury(&config, &assets[0].info) {
+        amount = assets[0].amount;
+    } else if is_fury(&config, &assets[1].info) {
+        amount = assets[1].amount;
+    }
+
+    // Prepare submessage for Increase Allowance
+    let increase_allowance_msg = Cw20ExecuteMsg::IncreaseAllowance {
+        spender: config.pool_pair_address,
+        amount: amount,
+        expires: None,
+    };
+    let exec_incr_allow = WasmMsg::Execute {
+        contract_addr: config.custom_token_address.to_string(),
+        msg: to_binary(&increase_allowance_msg).unwrap(),
+        funds: vec![],
+    };
+    let mut send_incr_allow: SubMsg = SubMsg::new(exec_incr_allow);
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(deps.storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    }
+    send_incr_allow.reply_on = ReplyOn::Always;
+    send_incr_allow.id = sub_req_id;
+    advance_pending_request(
+        deps.storage,
+        request_id,
+        sub_req_id,
+        PendingRequestStatus::Allowance,
+    )?;
+
+    resp = resp.add_submessage(send_incr_allow);
+
+    let pl_msg = PairExecuteMsg::ProvideLiquidity {
+        assets: assets,
+        slippage_tolerance: slippage_tolerance,
+        auto_stake: auto_stake,
+        receiver: receiver,
+    };
+
+    // let data_msg = format!("{:?}", pl_msg).into_bytes();
+
+    // Save the submessage_payload
+    SUB_MESSAGE_DETAILS.save(
+        deps.storage,
+        sub_req_id.to_string(),
+        &SubMessageDetails {
+            sub_req_id: sub_req_id.to_string(),
+            request_type: SubMessageType::ProvideLiquiditySubMsg,
+            next_action: SubMessageNextAction::ProvideLiquidity,
+            sub_message_payload: to_binary(&pl_msg)?,
+            funds: funds,
+            user_address: user_address,
+            is_fury_provided: is_fury_provided,
+            request_id: request_id,
+            term_id: None,
+        },
+    )?;
+
+    Ok(resp.add_attribute(
+        "action",
+        "Increase Allowance for proxy contract to Provide Liquidity",
+    ))
+}
+
+pub fn forward_provide_liquidity_to_astro(
+    deps: DepsMut,
+    env: Env,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    funds: Vec<Coin>,
+    request_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = load_request_config(deps.storage, request_id)?;
+
+    let mut funds_to_pass: Vec<Coin> = Vec::new();
+    for fund in funds {
+        let asset = Asset {
+            amount: fund.amount,
+            info: AssetInfo::NativeToken {
+                denom: fund.denom.clone(),
+            },
+        };
+        let c = Coin {
+            denom: fund.denom,
+            amount: fund
+                .amount
+                .checked_sub(asset.compute_tax(&deps.querier)?)
+                .unwrap(),
+        };
+        funds_to_pass.push(c);
+    }
+
+    let quote_token_amount = quote_amount(&config, &assets);
+    let pl_msg = PairExecuteMsg::ProvideLiquidity {
+        assets,
+        slippage_tolerance,
+        auto_stake,
+        receiver,
+    };
+    let exec = WasmMsg::Execute {
+        contract_addr: config.pool_pair_address.to_string(),
+        msg: to_binary(&pl_msg).unwrap(),
+        funds: funds_to_pass.clone(),
+    };
+    let mut send: SubMsg = SubMsg::new(exec);
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(deps.storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    }
+    send.id = sub_req_id;
+    send.reply_on = ReplyOn::Always;
+    let mut request = advance_pending_request(
+        deps.storage,
+        request_id,
+        sub_req_id,
+        PendingRequestStatus::ProvideLiquidity,
+    )?;
+    let mut resp = Response::new();
+    // A CW20 quote asset is pulled from the user and approved for the pair right before providing liquidity
+    if let AssetInfo::Token { contract_addr } = &config.quote_asset {
+        resp = resp
+            .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: request.user_address.clone(),
+                    recipient: env.contract.address.to_string(),
+                    amount: quote_token_amount,
+                })?,
+                funds: vec![],
+            }))
+            .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: config.pool_pair_address.clone(),
+                    amount: quote_token_amount,
+                    expires: None,
+                })?,
+                funds: vec![],
+            }));
+        request.quote_token_amount = quote_token_amount;
+        PENDING_REQUESTS.save(deps.storage, U64Key::new(request_id), &request)?;
+    }
+    // Save the submessage_payload so that the request is completed on reply
+    SUB_MESSAGE_DETAILS.save(
+        deps.storage,
+        sub_req_id.to_string(),
+        &SubMessageDetails {
+            sub_req_id: sub_req_id.to_string(),
+            request_type: SubMessageType::ProvideLiquiditySubMsg,
+            next_action: SubMessageNextAction::CompletePendingRequest,
+            sub_message_payload: to_binary(&pl_msg)?,
+            funds: funds_to_pass,
+            user_address: request.user_address,
+            is_fury_provided: FURY_PROVIDED,
+            request_id: request_id,
+            term_id: None,
+        },
+    )?;
+
+    resp = resp.add_submessage(send);
+    let data_msg = format!("provide liquidity details {:?}", pl_msg).into_bytes();
+    Ok(resp
+        .add_attribute("action", "Sending provide liquidity message")
+        .set_data(data_msg))
+}
+
+pub fn forward_swap_to_astro(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    offer_token: String,
+    received_message: Cw20HookMsg,
+    amount: Uint128,
+    funds_to_send: Vec<Coin>,
+    platform_fees: Uint128,
+    fury_fees: Uint128,
+    pair_id: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: config.pool_pair_address.clone(),
+        amount: amount,
+        msg: to_binary(&received_message)?,
+    };
+    let exec = WasmMsg::Execute {
+        contract_addr: offer_token.clone(),
+        msg: to_binary(&send_msg).unwrap(),
+        funds: funds_to_send,
+    };
+    // let mut send: SubMsg = SubMsg::new(exec);
+    // let mut sub_req_id = 1;
+    // if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+    //     req_id += 1;
+    //     SUB_REQ_ID.save(deps.storage, &req_id)?;
+    //     sub_req_id = req_id;
+    // } else {
+    //     SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    // }
+
+    // send.id = sub_req_id;
+    // send.reply_on = ReplyOn::Always;
+
+    let mut resp = Response::new();
+    // Add message to transfer the offered tokens
+    resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: offer_token,
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: info.sender.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount: amount,
+        })?,
+        funds: vec![],
+    }));
+    // resp = resp.add_submessage(send);
+    resp = resp.add_message(CosmosMsg::Wasm(exec));
+    //Add message to transfer platform fees to platform fee collector wallet
+    resp = resp.add_messages(collect_platform_fees(
+        deps.as_ref(),
+        &config,
+        info.sender.to_string(),
+        platform_fees,
+    )?);
+    resp = resp.add_messages(collect_fury_fees(
+        &config,
+        info.sender.to_string(),
+        fury_fees,
+    )?);
+    Ok(resp.add_attribute("action", "Forwarding swap message to pool pair address"))
+}
+
+pub fn provide_native_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    term_id: Option<u64>,
+    pair_id: Option<String>,
+    referrer: Option<Addr>,
+) -> Result<Response, ContractError> {
+    //Check if assets provided are native tokens
+    asset.info.check(deps.api)?;
+    //First check if platform fees are provided
+    let required_ust_fees: Uint128;
+    required_ust_fees = query_platform_fees(
+        deps.as_ref(),
+        env.clone(),
+        to_binary(&ExecuteMsg::ProvideNativeForReward {
+            asset: asset.clone(),
+            slippage_tolerance: slippage_tolerance.clone(),
+            auto_stake: auto_stake.clone(),
+            term_id: term_id,
+            pair_id: pair_id.clone(),
+            referrer: referrer.as_ref().map(|referrer| referrer.to_string()),
+        })?,
+        Some(info.sender.to_string()),
+    )?;
+    let config = load_config(deps.storage, pair_id.as_deref())?;
+    let mut fees = received_platform_fees(&config, &info.funds, required_ust_fees);
+    // A native investment is attached along with the fees, a CW20 one is pulled at the end
+    let mut funds_to_send = vec![];
+    if let AssetInfo::NativeToken { denom, .. } = &asset.info {
+        let native_tax = asset.compute_tax(&deps.querier)?;
+        fees = fees.checked_sub(asset.amount).unwrap();
+        fees = fees.checked_sub(native_tax).unwrap();
+        funds_to_send = vec![Coin {
+            denom: denom.to_string(),
+            amount: asset.amount,
+        }];
+    }
+    if fees < required_ust_fees {
+        return Err(ContractError::InsufficientFees {
+            required: required_ust_fees,
+            received: fees,
+        });
+    }
+    // Platform fees received is good, now proceed
+
+    let assets = [
+        Asset {
+            info: config.quote_asset.clone(),
+            amount: asset.amount,
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: config.custom_token_address,
+            },
+            amount: Uint128::from(0u128),
+        },
+    ];
+
+    let receiver: Option<String>;
+    receiver = Some(config.native_investment_receive_wallet.to_string());
+
+    let user_address = info.sender.into_string();
+    let request_id = create_pending_request(
+        deps.storage,
+        user_address.clone(),
+        funds_to_send.clone(),
+        Uint128::zero(),
+        pair_id,
+        referrer,
+    )?;
+    transfer_custom_assets_from_funds_owner_to_proxy(
+        deps,
+        env,
+        assets,
+        slippage_tolerance,
+        auto_stake,
+        receiver,
+        funds_to_send,
+        user_address,
+        NO_FURY_PROVIDED,
+        required_ust_fees,
+        request_id,
+        term_id,
+    )
+}
+
+pub fn transfer_native_assets_to_native_investment_receive_wallet(
+    mut deps: DepsMut,
+    env: Env,
+    assets: [Asset; 2],
+    receiver: Option<String>,
+    funds: Vec<Coin>,
+    user_address: String,
+    request_id: u64,
+) -> Result<Response, ContractError> {
+    // Bonded Fury has been received, this is the last step of the request
+    let config = load_request_config(deps.storage, request_id)?;
+    let request = PENDING_REQUESTS.load(deps.storage, U64Key::new(request_id))?;
+    PENDING_REQUESTS.remove(deps.storage, U64Key::new(request_id));
+    let fee_msgs = collect_pending_request_fees(deps.branch(), &env, &config, &request)?;
+
+    // A CW20 quote asset goes straight from the user to the receive wallet
+    if let AssetInfo::Token { contract_addr } = &config.quote_asset {
+        return Ok(Response::new()
+            .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: user_address,
+                    recipient: receiver.unwrap(),
+                    amount: quote_amount(&config, &assets),
+                })?,
+                funds: vec![],
+            }))
+            .add_messages(fee_msgs));
+    }
+
+    let mut funds_to_pass: Vec<Coin> = Vec::new();
+    for fund in funds {
+        let asset = Asset {
+            amount: fund.amount,
+            info: AssetInfo::NativeToken {
+                denom: fund.denom.clone(),
+            },
+        };
+        let c = Coin {
+            denom: fund.denom,
+            amount: fund
+                .amount
+                .checked_sub(asset.compute_tax(&deps.querier)?)
+                .unwrap(),
+        };
+        funds_to_pass.push(c);
+    }
+
+    let resp = Response::new();
+    Ok(resp
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: receiver.unwrap(),
+            amount: funds_to_pass,
+        }))
+        .add_messages(fee_msgs))
+}
+
+/// Fury bonded for an investment and the terms it is issued under
+struct BondQuote {
+    bond_type: BondType,
+    /// Reward wallet the bonded Fury is fetched from
+    funds_owner: String,
+    fury_amount: Uint128,
+    /// 100% = 10000
+    discount_rate: u16,
+    bonding_period: u64,
+    vesting_schedule: VestingSchedule,
+}
+
+/// Prices the bond of a pair or native investment. A bond term picked by the user overrides
+/// the defaults of the bond type
+fn quote_bond(
+    deps: Deps,
+    config: &Config,
+    ust_amount_provided: Uint128,
+    fury_amount_provided: Uint128,
+    is_fury_provided: bool,
+    term_id: Option<u64>,
+) -> Result<BondQuote, ContractError> {
+    let mut fury_equiv_for_ust =
+        get_bond_fury_equivalent_to_ust(deps, config, ust_amount_provided)?;
+    let fury_pre_discount;
+    let mut quote;
+    if is_fury_provided {
+        if fury_equiv_for_ust > fury_amount_provided {
+            fury_equiv_for_ust = fury_amount_provided;
+        }
+        fury_pre_discount = Uint128::from(2u128) * fury_equiv_for_ust;
+        quote = BondQuote {
+            bond_type: BondType::Pair,
+            funds_owner: config.pair_fury_reward_wallet.to_string(),
+            fury_amount: Uint128::zero(),
+            discount_rate: config.pair_discount_rate,
+            bonding_period: config.pair_bonding_period_in_sec,
+            vesting_schedule: config.pair_vesting_schedule.clone(),
+        };
+    } else {
+        fury_pre_discount = fury_equiv_for_ust;
+        quote = BondQuote {
+            bond_type: BondType::Native,
+            funds_owner: config.native_investment_reward_wallet.to_string(),
+            fury_amount: Uint128::zero(),
+            discount_rate: config.native_discount_rate,
+            bonding_period: config.native_bonding_period_in_sec,
+            vesting_schedule: config.native_vesting_schedule.clone(),
+        };
+    }
+    if let Some(term_id) = term_id {
+        let term = load_active_bond_term(deps.storage, term_id, quote.bond_type)?;
+        quote.discount_rate = term.discount_rate;
+        quote.bonding_period = term.bonding_period_in_sec;
+        if let Some(term_vesting_schedule) = term.vesting_schedule {
+            quote.vesting_schedule = term_vesting_schedule;
+        }
+    }
+    let discounted_rate = 10000u16 - quote.discount_rate;
+    quote.fury_amount = fury_pre_discount
+        .checked_mul(Uint128::from(10000u128))
+        .unwrap_or_default()
+        .checked_div(Uint128::from(discounted_rate))
+        .unwrap_or_default();
+    Ok(quote)
+}
+
+pub fn transfer_custom_assets_from_funds_owner_to_proxy(
+    deps: DepsMut,
+    env: Env,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    funds: Vec<Coin>,
+    user_address: String,
+    is_fury_provided: bool,
+    platform_fees: Uint128,
+    request_id: u64,
+    term_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = load_request_config(deps.storage, request_id)?;
+    let mut fury_amount_provided = Uint128::zero();
+    let mut ust_amount_provided = Uint128::zero();
+    if is_fury_provided {
+        if is_fury(&config, &assets[0].info) {
             fury_amount_provided = assets[0].amount;
             ust_amount_provided = assets[1].amount;
-        } else if !assets[1].info.is_native_token() {
+        } else if is_fury(&config, &assets[1].info) {
             fury_amount_provided = assets[1].amount;
             ust_amount_provided = assets[0].amount;
         }
     } else {
-        if !assets[0].info.is_native_token() {
-            ust_amount_provided = assets[1].amount;
-        } else if !assets[1].info.is_native_token() {
-            ust_amount_provided = assets[0].amount;
+        if is_fury(&config, &assets[0].info) {
+            ust_amount_provided = assets[1].amount;
+        } else if is_fury(&config, &assets[1].info) {
+            ust_amount_provided = assets[0].amount;
+        }
+    }
+
+    let mut resp = Response::new();
+
+    let quote = quote_bond(
+        deps.as_ref(),
+        &config,
+        ust_amount_provided,
+        fury_amount_provided,
+        is_fury_provided,
+        term_id,
+    )?;
+    let total_fury_amount = quote.fury_amount;
+    let funds_owner = quote.funds_owner;
+    let bond_type = quote.bond_type;
+
+    // Get the existing bonded_rewards_details for this user
+    let bonded_rewards_details = read_user_bonds(deps.storage, &user_address)?;
+    if config.max_bonding_limit_per_user <= bonded_rewards_details.len() as u64 {
+        return Err(ContractError::BondLimitReached { user_address });
+    }
+
+    let mut bonding_start_timestamp = Timestamp::from_seconds(0u64);
+    if config.swap_opening_date < env.block.time {
+        bonding_start_timestamp = env.block.time;
+    }
+
+    // Stop issuing bonds once the capacity of the bond type or the reward wallet is exhausted
+    consume_bond_capacity(
+        deps.storage,
+        &config,
+        bond_type,
+        total_fury_amount,
+        env.block.time,
+    )?;
+    let reward_wallet_available = query_reward_wallet_available(
+        deps.as_ref(),
+        &config,
+        funds_owner.clone(),
+        env.contract.address.to_string(),
+    )?;
+    if reward_wallet_available < total_fury_amount {
+        return Err(ContractError::InsufficientRewardWalletFunds {
+            required: total_fury_amount,
+            available: reward_wallet_available,
+        });
+    }
+
+    let bond_id = BOND_ID.may_load(deps.storage)?.unwrap_or_default() + 1;
+    BOND_ID.save(deps.storage, &bond_id)?;
+
+    let bond = BondedRewardsDetails {
+        bond_id: bond_id,
+        user_address: user_address.to_string(),
+        bond_type: bond_type,
+        bonded_amount: total_fury_amount,
+        bonding_period: quote.bonding_period,
+        bonding_start_timestamp: bonding_start_timestamp,
+        vesting_schedule: quote.vesting_schedule,
+        claimed_amount: Uint128::zero(),
+        term_id: term_id,
+    };
+    record_bond_issued(deps.storage, &bond, ust_amount_provided)?;
+    BONDED_REWARDS_DETAILS.save(deps.storage, (&user_address, U64Key::new(bond_id)), &bond)?;
+    BOND_OWNERS.save(deps.storage, U64Key::new(bond_id), &user_address)?;
+
+    // Prepare submessage for Execute transfer_from funds_owner to proxy contract
+    let transfer_from_msg = Cw20ExecuteMsg::TransferFrom {
+        owner: funds_owner.clone(),
+        recipient: env.contract.address.to_string(),
+        amount: total_fury_amount,
+    };
+    let exec_transfer_from = WasmMsg::Execute {
+        contract_addr: config.custom_token_address.to_string(),
+        msg: to_binary(&transfer_from_msg).unwrap(),
+        funds: vec![],
+    };
+    let mut send_transfer_from: SubMsg = SubMsg::new(exec_transfer_from);
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(deps.storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    }
+    send_transfer_from.reply_on = ReplyOn::Always;
+    send_transfer_from.id = sub_req_id;
+    resp = resp.add_submessage(send_transfer_from);
+    let mut request = advance_pending_request(
+        deps.storage,
+        request_id,
+        sub_req_id,
+        PendingRequestStatus::TreasuryTransfer,
+    )?;
+    request.treasury_address = Some(addr_validate_to_lower(deps.api, &funds_owner)?);
+    request.treasury_fury_amount = total_fury_amount;
+    request.bond_id = Some(bond_id);
+    request.ust_amount = ust_amount_provided;
+    // Fees of a pair bond were recorded on the first step, they are collected and the referrer
+    // is credited once the request completes
+    if !platform_fees.is_zero() {
+        request.referral_fees = referral_share(&config, request.referrer.as_ref(), platform_fees);
+        request.platform_fees = platform_fees;
+    }
+    PENDING_REQUESTS.save(deps.storage, U64Key::new(request_id), &request)?;
+
+    let pl_msg = PairExecuteMsg::ProvideLiquidity {
+        assets: assets,
+        slippage_tolerance: slippage_tolerance,
+        auto_stake: auto_stake,
+        receiver: receiver,
+    };
+
+    // Save the submessage_payload
+    if is_fury_provided {
+        SUB_MESSAGE_DETAILS.save(
+            deps.storage,
+            sub_req_id.to_string(),
+            &SubMessageDetails {
+                sub_req_id: sub_req_id.to_string(),
+                request_type: SubMessageType::ProvideLiquiditySubMsg,
+                next_action: SubMessageNextAction::IncreaseAllowance,
+                sub_message_payload: to_binary(&pl_msg)?,
+                funds: funds,
+                user_address: user_address,
+                is_fury_provided: is_fury_provided,
+                request_id: request_id,
+                term_id: term_id,
+            },
+        )?;
+    } else {
+        // Save the submessage_payload
+        SUB_MESSAGE_DETAILS.save(
+            deps.storage,
+            sub_req_id.to_string(),
+            &SubMessageDetails {
+                sub_req_id: sub_req_id.to_string(),
+                request_type: SubMessageType::ProvideLiquiditySubMsg,
+                next_action: SubMessageNextAction::TransferToNativeInvestmentReceiveWallet,
+                sub_message_payload: to_binary(&pl_msg)?,
+                funds: funds,
+                user_address: user_address,
+                is_fury_provided: is_fury_provided,
+                request_id: request_id,
+                term_id: term_id,
+            },
+        )?;
+    }
+
+    Ok(resp
+        .add_attribute(
+            "action",
+            "Transferring fury from treasury funds owner to proxy",
+        )
+        .add_attribute("bond_id", bond_id.to_string()))
+}
+
+pub fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    next_action: SubMessageNextAction,
+    platform_fees: Uint128,
+    term_id: Option<u64>,
+    pair_id: Option<String>,
+    referrer: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut resp = Response::new();
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    // Get the amount of Fury tokens to be specified in transfer_from and increase_allowance
+    let mut amount = Uint128::zero();
+    if is_fury(&config, &assets[0].info) {
+        amount = assets[0].amount;
+    } else if is_fury(&config, &assets[1].info) {
+        amount = assets[1].amount;
+    }
+
+    let user_address = info.sender.into_string();
+
+    // Prepare submessage for Execute transfer_from user wallet to proxy contract
+    let transfer_from_msg = Cw20ExecuteMsg::TransferFrom {
+        owner: user_address.clone(),
+        recipient: env.contract.address.to_string(),
+        amount: amount,
+    };
+    let exec_transfer_from = WasmMsg::Execute {
+        contract_addr: config.custom_token_address.to_string(),
+        msg: to_binary(&transfer_from_msg).unwrap(),
+        funds: vec![],
+    };
+    let mut send_transfer_from: SubMsg = SubMsg::new(exec_transfer_from);
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(deps.storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    }
+    send_transfer_from.reply_on = ReplyOn::Always;
+    send_transfer_from.id = sub_req_id;
+    resp = resp.add_submessage(send_transfer_from);
+    let request_id = create_pending_request(
+        deps.storage,
+        user_address.clone(),
+        info.funds.clone(),
+        amount,
+        pair_id,
+        referrer.clone(),
+    )?;
+    let mut request = advance_pending_request(
+        deps.storage,
+        request_id,
+        sub_req_id,
+        PendingRequestStatus::UserTransfer,
+    )?;
+    let pl_msg = PairExecuteMsg::ProvideLiquidity {
+        assets: assets,
+        slippage_tolerance: slippage_tolerance,
+        auto_stake: auto_stake,
+        receiver: receiver,
+    };
+    // Save the submessage_payload
+    SUB_MESSAGE_DETAILS.save(
+        deps.storage,
+        sub_req_id.to_string(),
+        &SubMessageDetails {
+            sub_req_id: sub_req_id.to_string(),
+            request_type: SubMessageType::ProvideLiquiditySubMsg,
+            next_action: next_action,
+            sub_message_payload: to_binary(&pl_msg)?,
+            funds: info.funds,
+            user_address: user_address.clone(),
+            is_fury_provided: FURY_PROVIDED,
+            request_id: request_id,
+            term_id: term_id,
+        },
+    )?;
+    // The platform fees are collected and the referrer is credited once the request completes
+    request.referral_fees = referral_share(&config, referrer.as_ref(), platform_fees);
+    request.platform_fees = platform_fees;
+    PENDING_REQUESTS.save(deps.storage, U64Key::new(request_id), &request)?;
+    Ok(resp.add_attribute("action", "Transferring tokens for Provide Liquidity"))
+}
+
+/// Withdraws the received LP tokens from their pool. The pair refunds the assets to the proxy,
+/// which forwards them to the receiver on reply
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    received_message: Cw20ReceiveMsg,
+    min_assets: Vec<Asset>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // The LP token sending the message tells which pool the liquidity is withdrawn from
+    let mut pool = None;
+    if info.sender == config.liquidity_token {
+        pool = Some((None, config.pool_pair_address));
+    }
+    for item in PAIRS.range(deps.storage, None, None, Order::Ascending) {
+        let (pair_id, pair) = item?;
+        if info.sender == pair.liquidity_token {
+            let pair_id = String::from_utf8(pair_id).map_err(StdError::from)?;
+            pool = Some((Some(pair_id), pair.pool_pair_address));
+        }
+    }
+    let (pair_id, pool_pair_address) = pool.ok_or(ContractError::Unauthorized {})?;
+    let receiver = match receiver {
+        Some(receiver) => addr_validate_to_lower(deps.api, &receiver)?.to_string(),
+        None => received_message.sender.clone(),
+    };
+    let wl_msg = Cw20ExecuteMsg::Send {
+        contract: pool_pair_address,
+        amount: received_message.amount,
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {})?,
+    };
+    let exec = WasmMsg::Execute {
+        contract_addr: info.sender.to_string(),
+        msg: to_binary(&wl_msg).unwrap(),
+        funds: info.funds,
+    };
+
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(deps.storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    }
+    SUB_MESSAGE_DETAILS.save(
+        deps.storage,
+        sub_req_id.to_string(),
+        &SubMessageDetails {
+            sub_req_id: sub_req_id.to_string(),
+            request_type: SubMessageType::WithdrawLiquiditySubMsg,
+            next_action: SubMessageNextAction::ForwardWithdrawnAssets,
+            sub_message_payload: to_binary(&WithdrawLiquidityDetails {
+                receiver,
+                min_assets,
+                pair_id,
+            })?,
+            funds: vec![],
+            user_address: received_message.sender,
+            is_fury_provided: false,
+            term_id: None,
+            request_id: 0,
+        },
+    )?;
+
+    let resp = Response::new();
+    let data_msg = format!("Withdraw {:?}", wl_msg).into_bytes();
+    Ok(resp
+        .add_submessage(SubMsg::reply_on_success(exec, sub_req_id))
+        .add_attribute("action", "Forwarding withdraw message to lptoken address")
+        .set_data(data_msg))
+}
+
+/// Forwards the assets refunded by the pair to the receiver, taking the platform fees out of
+/// them and checking them against the minimum amounts
+fn forward_withdrawn_assets(
+    deps: DepsMut,
+    details: WithdrawLiquidityDetails,
+    events: &[Event],
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, details.pair_id.as_deref())?;
+    let fee_rate = config.platform_fees + config.transaction_fees;
+
+    let mut net_assets = vec![];
+    let mut fee_msgs = vec![];
+    for refund_asset in withdrawn_assets(&config, events)? {
+        // The pair pays the tax of native assets out of the refund
+        let received = refund_asset
+            .amount
+            .checked_sub(refund_asset.compute_tax(&deps.querier)?)
+            .map_err(StdError::from)?;
+        let mut platform_fees = received.multiply_ratio(fee_rate.u128(), HUNDRED_PERCENT);
+        if is_fury(&config, &refund_asset.info) {
+            platform_fees = platform_fees
+                - platform_fees.multiply_ratio(config.fury_fee_discount as u128, HUNDRED_PERCENT);
+        }
+        fee_msgs.extend(distribute_fees(
+            deps.as_ref(),
+            &config,
+            &refund_asset.info,
+            platform_fees,
+        )?);
+        net_assets.push(Asset {
+            info: refund_asset.info,
+            amount: received - platform_fees,
+        });
+    }
+    for minimum in details.min_assets {
+        let received = net_assets
+            .iter()
+            .find(|asset| asset.info == minimum.info)
+            .map(|asset| asset.amount)
+            .unwrap_or_default();
+        if received < minimum.amount {
+            return Err(ContractError::WithdrawnAssetBelowMinimum {
+                minimum: minimum.to_string(),
+                received: received.to_string(),
+            });
+        }
+    }
+
+    let mut resp = Response::new().add_attribute("action", "Forwarding withdrawn liquidity");
+    for asset in net_assets {
+        if asset.amount.is_zero() {
+            continue;
+        }
+        resp = resp
+            .add_attribute("withdrawn_asset", asset.to_string())
+            .add_message(asset.into_msg(&deps.querier, Addr::unchecked(&details.receiver))?);
+    }
+    Ok(resp.add_messages(fee_msgs))
+}
+
+/// Reads the assets the pair refunds on a liquidity withdrawal, which it emits as
+/// "<amount><asset>, <amount><asset>"
+fn withdrawn_assets(config: &Config, events: &[Event]) -> StdResult<Vec<Asset>> {
+    let refund_assets = pair_event_attribute(events, &config.pool_pair_address, "refund_assets")?;
+    let pool_assets = [fury_asset_info(config), config.quote_asset.clone()];
+    refund_assets
+        .split(", ")
+        .map(|refund_asset| {
+            let split_at = refund_asset
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(refund_asset.len());
+            let (amount, asset) = refund_asset.split_at(split_at);
+            let info = pool_assets
+                .iter()
+                .find(|info| info.to_string() == asset)
+                .ok_or_else(|| StdError::generic_err("Invalid refund_assets in reply"))?;
+            let amount = amount
+                .parse::<u128>()
+                .map_err(|_| StdError::generic_err("Invalid refund_assets in reply"))?;
+            Ok(Asset {
+                info: info.clone(),
+                amount: Uint128::from(amount),
+            })
+        })
+        .collect()
+}
+
+fn claim_investment_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receiver: String,
+    withdrawal_amount: Uint128,
+    pay_fees_in_fury: bool,
+) -> Result<Response, ContractError> {
+    //Check if platform fees provided is sufficient
+    let required_ust_fees: Uint128;
+    required_ust_fees = query_platform_fees(
+        deps.as_ref(),
+        env.clone(),
+        to_binary(&ExecuteMsg::RewardClaim {
+            receiver: receiver.clone(),
+            withdrawal_amount: withdrawal_amount.clone(),
+            pay_fees_in_fury: Some(pay_fees_in_fury),
+        })?,
+        Some(info.sender.to_string()),
+    )?;
+    let config = CONFIG.load(deps.storage)?;
+    let (platform_fees, fury_fees) =
+        platform_fees_by_asset(deps.as_ref(), &config, required_ust_fees, pay_fees_in_fury)?;
+    let fees = received_platform_fees(&config, &info.funds, platform_fees);
+    if fees < platform_fees {
+        return Err(ContractError::InsufficientFees {
+            required: platform_fees,
+            received: fees,
+        });
+    }
+
+    let receiver_addr = deps.api.addr_validate(&receiver)?;
+    //Check if withdrawer is same as invoker
+    if receiver_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.time < config.swap_opening_date {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Swap Opening not reached {:?}",
+            config.swap_opening_date
+        ))));
+    }
+
+    let FAR_IN_FUTURE = env.block.time.plus_seconds(2000 * 24 * 60 * 60).seconds();
+
+    let action = "claim_investment_reward".to_string();
+    let mut claimable_total = Uint128::zero();
+    let mut amount_remaining = withdrawal_amount.clone();
+
+    // parse bonding to release the vested part of each bond in order
+    let mut earliest = FAR_IN_FUTURE;
+    let mut earliest_amount = Uint128::zero();
+
+    let bonds = read_user_bonds(deps.storage, &receiver)?;
+    for mut bond in bonds {
+        let maturity = bond_start_timestamp(&config, &bond)
+            .plus_seconds(bond.bonding_period)
+            .seconds();
+        if maturity < earliest {
+            earliest = maturity;
+            earliest_amount = bond.bonded_amount - bond.claimed_amount;
+        }
+        let claimable = claimable_amount(&config, &bond, env.block.time);
+        claimable_total += claimable;
+        let claimed_now = claimable.min(amount_remaining);
+        amount_remaining -= claimed_now;
+        bond.claimed_amount += claimed_now;
+        let fully_claimed = bond.claimed_amount >= bond.bonded_amount;
+        record_bond_released(deps.storage, &bond, claimed_now, fully_claimed)?;
+        if fully_claimed {
+            remove_bond(deps.storage, receiver.clone(), bond.bond_id)?;
+        } else {
+            BONDED_REWARDS_DETAILS.save(
+                deps.storage,
+                (&receiver, U64Key::new(bond.bond_id)),
+                &bond,
+            )?;
+        }
+    }
+
+    if claimable_total == Uint128::zero() {
+        let message;
+        if earliest < FAR_IN_FUTURE {
+            message = format!(
+                "Earliest Withdrawal Amount {:?} at {:?}",
+                earliest_amount, earliest
+            );
+        } else {
+            message = format!("No Bonded Rewards");
+        }
+        return Err(ContractError::Std(StdError::generic_err(message)));
+    } else {
+        if amount_remaining > Uint128::zero() {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Withdraw Amount requested is more than Claimable {:?}",
+                claimable_total
+            ))));
+        }
+    }
+
+    let rsp = transfer_claimed_rewards(
+        deps.as_ref(),
+        config,
+        info.sender.to_string(),
+        platform_fees,
+        fury_fees,
+        receiver,
+        withdrawal_amount,
+    )?;
+    return Ok(rsp.add_attribute("action", action));
+}
+
+/// Claims everything vested so far from all bonds of the sender, or from a single bond when bond_id is given
+fn claim_vested_bonds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bond_id: Option<u64>,
+    pay_fees_in_fury: bool,
+) -> Result<Response, ContractError> {
+    let user_address = info.sender.to_string();
+    let claim_msg = match bond_id {
+        Some(bond_id) => ExecuteMsg::ClaimBond {
+            bond_id,
+            pay_fees_in_fury: Some(pay_fees_in_fury),
+        },
+        None => ExecuteMsg::ClaimAllMatured {
+            pay_fees_in_fury: Some(pay_fees_in_fury),
+        },
+    };
+    //Check if platform fees provided is sufficient
+    let required_ust_fees = query_platform_fees(
+        deps.as_ref(),
+        env.clone(),
+        to_binary(&claim_msg)?,
+        Some(user_address.clone()),
+    )?;
+    let config = CONFIG.load(deps.storage)?;
+    let (platform_fees, fury_fees) =
+        platform_fees_by_asset(deps.as_ref(), &config, required_ust_fees, pay_fees_in_fury)?;
+    let fees = received_platform_fees(&config, &info.funds, platform_fees);
+    if fees < platform_fees {
+        return Err(ContractError::InsufficientFees {
+            required: platform_fees,
+            received: fees,
+        });
+    }
+    if env.block.time < config.swap_opening_date {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Swap Opening not reached {:?}",
+            config.swap_opening_date
+        ))));
+    }
+
+    let mut bond_found = false;
+    let mut claimed_amount = Uint128::zero();
+    let bonds = read_user_bonds(deps.storage, &user_address)?;
+    for mut bond in bonds {
+        if bond_id.map_or(false, |id| id != bond.bond_id) {
+            continue;
+        }
+        bond_found = true;
+        let claimable = claimable_amount(&config, &bond, env.block.time);
+        bond.claimed_amount += claimable;
+        claimed_amount += claimable;
+        let fully_claimed = bond.claimed_amount >= bond.bonded_amount;
+        record_bond_released(deps.storage, &bond, claimable, fully_claimed)?;
+        if fully_claimed {
+            remove_bond(deps.storage, user_address.clone(), bond.bond_id)?;
+        } else {
+            BONDED_REWARDS_DETAILS.save(
+                deps.storage,
+                (&user_address, U64Key::new(bond.bond_id)),
+                &bond,
+            )?;
+        }
+    }
+    if let Some(bond_id) = bond_id {
+        if !bond_found {
+            return Err(ContractError::BondNotFound { bond_id });
+        }
+    }
+    if claimed_amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "No matured Bonded Rewards to claim",
+        )));
+    }
+
+    let rsp = transfer_claimed_rewards(
+        deps.as_ref(),
+        config,
+        user_address.clone(),
+        platform_fees,
+        fury_fees,
+        user_address,
+        claimed_amount,
+    )?;
+    let action = match bond_id {
+        Some(_) => "claim_bond",
+        None => "claim_all_matured",
+    };
+    Ok(rsp.add_attribute("action", action))
+}
+
+/// Releases a bond of the sender before maturity, the penalty is sent to the configured destination
+fn early_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bond_id: u64,
+    pay_fees_in_fury: bool,
+) -> Result<Response, ContractError> {
+    let user_address = info.sender.to_string();
+    //Check if platform fees provided is sufficient
+    let required_ust_fees = query_platform_fees(
+        deps.as_ref(),
+        env.clone(),
+        to_binary(&ExecuteMsg::EarlyUnbond {
+            bond_id,
+            pay_fees_in_fury: Some(pay_fees_in_fury),
+        })?,
+        Some(user_address.clone()),
+    )?;
+    let config = CONFIG.load(deps.storage)?;
+    let (platform_fees, fury_fees) =
+        platform_fees_by_asset(deps.as_ref(), &config, required_ust_fees, pay_fees_in_fury)?;
+    let fees = received_platform_fees(&config, &info.funds, platform_fees);
+    if fees < platform_fees {
+        return Err(ContractError::InsufficientFees {
+            required: platform_fees,
+            received: fees,
+        });
+    }
+    if config.early_unbond_penalty_rate.is_none() {
+        return Err(ContractError::EarlyUnbondDisabled {});
+    }
+    if env.block.time < config.swap_opening_date {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Swap Opening not reached {:?}",
+            config.swap_opening_date
+        ))));
+    }
+
+    let bond = remove_bond(deps.storage, user_address.clone(), bond_id)?
+        .ok_or(ContractError::BondNotFound { bond_id })?;
+    let (payout, penalty) = early_unbond_amounts(&config, &bond, env.block.time);
+    if payout.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Early unbond of bond {} pays out nothing",
+            bond_id
+        ))));
+    }
+    record_bond_released(
+        deps.storage,
+        &bond,
+        bond.bonded_amount - bond.claimed_amount,
+        true,
+    )?;
+
+    let penalty_msg = early_unbond_penalty_msg(&config, bond.bond_type, penalty)?;
+    let mut rsp = transfer_claimed_rewards(
+        deps.as_ref(),
+        config,
+        user_address.clone(),
+        platform_fees,
+        fury_fees,
+        user_address,
+        payout,
+    )?;
+    if !penalty.is_zero() {
+        rsp = rsp.add_message(penalty_msg);
+    }
+    Ok(rsp
+        .add_attribute("action", "early_unbond")
+        .add_attribute("bond_id", bond_id.to_string())
+        .add_attribute("penalty", penalty.to_string()))
+}
+
+/// Splits what is left of a bond into the payout and the penalty of an early unbond.
+/// The penalty applies to the unvested part and shrinks linearly to zero at maturity.
+fn early_unbond_amounts(
+    config: &Config,
+    bond: &BondedRewardsDetails,
+    now: Timestamp,
+) -> (Uint128, Uint128) {
+    let remaining = bond.bonded_amount - bond.claimed_amount;
+    let start = bond_start_timestamp(config, bond).seconds();
+    let maturity = start + bond.bonding_period;
+    if maturity <= now.seconds() {
+        return (remaining, Uint128::zero());
+    }
+    let unvested = bond.bonded_amount - vested_amount(config, bond, now);
+    let time_to_maturity = maturity - now.seconds().max(start);
+    let penalty_rate = config.early_unbond_penalty_rate.unwrap_or_default() as u128;
+    let penalty = unvested
+        .multiply_ratio(penalty_rate, HUNDRED_PERCENT)
+        .multiply_ratio(time_to_maturity, bond.bonding_period);
+    (remaining - penalty, penalty)
+}
+
+/// Sends the Fury forfeited by an early unbond to the configured destination
+fn early_unbond_penalty_msg(
+    config: &Config,
+    bond_type: BondType,
+    penalty: Uint128,
+) -> StdResult<CosmosMsg> {
+    let penalty_msg = match &config.early_unbond_penalty_destination {
+        PenaltyDestination::RewardWallet => {
+            let reward_wallet = match bond_type {
+                BondType::Pair => &config.pair_fury_reward_wallet,
+                BondType::Native => &config.native_investment_reward_wallet,
+            };
+            Cw20ExecuteMsg::Transfer {
+                recipient: reward_wallet.to_string(),
+                amount: penalty,
+            }
+        }
+        PenaltyDestination::Burn => Cw20ExecuteMsg::Burn { amount: penalty },
+        PenaltyDestination::Address { address } => Cw20ExecuteMsg::Transfer {
+            recipient: address.clone(),
+            amount: penalty,
+        },
+    };
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.custom_token_address.to_string(),
+        msg: to_binary(&penalty_msg)?,
+        funds: vec![],
+    }))
+}
+
+fn fury_asset_info(config: &Config) -> AssetInfo {
+    AssetInfo::Token {
+        contract_addr: config.custom_token_address.clone(),
+    }
+}
+
+/// Returns true if the asset is the Fury token of this proxy
+fn is_fury(config: &Config, asset_info: &AssetInfo) -> bool {
+    match asset_info {
+        AssetInfo::Token { contract_addr } => *contract_addr == config.custom_token_address,
+        AssetInfo::NativeToken { .. } => false,
+    }
+}
+
+/// Amount of Fury provided in the given assets
+fn fury_amount(config: &Config, assets: &[Asset]) -> Uint128 {
+    assets
+        .iter()
+        .filter(|asset| is_fury(config, &asset.info))
+        .map(|asset| asset.amount)
+        .sum()
+}
+
+/// Amount of quote asset provided in the given assets
+fn quote_amount(config: &Config, assets: &[Asset]) -> Uint128 {
+    assets
+        .iter()
+        .filter(|asset| asset.info == config.quote_asset)
+        .map(|asset| asset.amount)
+        .sum()
+}
+
+/// Platform fees attached to a message. A CW20 quote asset is pulled from the sender,
+/// so the required amount is assumed and the transfer fails if it is not approved
+fn received_platform_fees(config: &Config, funds: &[Coin], required: Uint128) -> Uint128 {
+    match &config.quote_asset {
+        AssetInfo::NativeToken { denom } => funds
+            .iter()
+            .filter(|fund| &fund.denom == denom)
+            .map(|fund| fund.amount)
+            .sum(),
+        AssetInfo::Token { .. } => required,
+    }
+}
+
+/// Splits fees across the fee collectors by share, the last collector gets the rounding remainder
+fn split_fees(config: &Config, amount: Uint128) -> Vec<(String, Uint128)> {
+    if amount.is_zero() {
+        return vec![];
+    }
+    if config.fee_collectors.is_empty() {
+        return vec![(config.platform_fees_collector_wallet.to_string(), amount)];
+    }
+    let mut remaining = amount;
+    let mut shares = vec![];
+    for (i, collector) in config.fee_collectors.iter().enumerate() {
+        let share_amount = if i + 1 == config.fee_collectors.len() {
+            remaining
+        } else {
+            amount.multiply_ratio(collector.share as u128, HUNDRED_PERCENT)
+        };
+        remaining -= share_amount;
+        let recipient = match &collector.recipient {
+            FeeRecipient::Address { address } => address.clone(),
+            FeeRecipient::Maker => match &config.maker_address {
+                Some(maker_address) => maker_address.to_string(),
+                None => config.platform_fees_collector_wallet.to_string(),
+            },
+        };
+        if !share_amount.is_zero() {
+            shares.push((recipient, share_amount));
+        }
+    }
+    shares
+}
+
+/// Messages moving the platform fees to the fee collectors, either from the attached funds
+/// or pulled from the payer when the quote asset is a CW20 token
+fn collect_platform_fees(
+    deps: Deps,
+    config: &Config,
+    payer: String,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut fee_msgs = vec![];
+    for (recipient, share_amount) in split_fees(config, amount) {
+        let fee_msg = match &config.quote_asset {
+            AssetInfo::NativeToken { .. } => {
+                let pf_asset = Asset {
+                    info: config.quote_asset.clone(),
+                    amount: share_amount,
+                };
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: recipient,
+                    amount: vec![pf_asset.deduct_tax(&deps.querier)?],
+                })
+            }
+            AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: payer.clone(),
+                    recipient,
+                    amount: share_amount,
+                })?,
+                funds: vec![],
+            }),
+        };
+        fee_msgs.push(fee_msg);
+    }
+    Ok(fee_msgs)
+}
+
+/// Platform fees in Fury, priced through the pool with the Fury fee discount applied
+fn fury_platform_fees(deps: Deps, config: &Config, platform_fees: Uint128) -> StdResult<Uint128> {
+    if platform_fees.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    let fury_fees = get_fury_equivalent_to_ust(deps, config, platform_fees)?;
+    Ok(fury_fees - fury_fees.multiply_ratio(config.fury_fee_discount as u128, HUNDRED_PERCENT))
+}
+
+/// Splits the required platform fees into the part paid in the quote asset and the part paid in Fury
+fn platform_fees_by_asset(
+    deps: Deps,
+    config: &Config,
+    required_fees: Uint128,
+    pay_fees_in_fury: bool,
+) -> StdResult<(Uint128, Uint128)> {
+    if pay_fees_in_fury {
+        Ok((
+            Uint128::zero(),
+            fury_platform_fees(deps, config, required_fees)?,
+        ))
+    } else {
+        Ok((required_fees, Uint128::zero()))
+    }
+}
+
+/// Messages pulling the platform fees paid in Fury from the payer to the fee collectors
+fn collect_fury_fees(config: &Config, payer: String, amount: Uint128) -> StdResult<Vec<CosmosMsg>> {
+    split_fees(config, amount)
+        .into_iter()
+        .map(|(recipient, share_amount)| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.custom_token_address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: payer.clone(),
+                    recipient,
+                    amount: share_amount,
+                })?,
+                funds: vec![],
+            }))
+        })
+        .collect()
+}
+
+/// Messages sending platform fees held by the proxy to the fee collectors
+fn distribute_fees(
+    deps: Deps,
+    config: &Config,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    split_fees(config, amount)
+        .into_iter()
+        .map(|(recipient, share_amount)| {
+            Asset {
+                info: asset_info.clone(),
+                amount: share_amount,
+            }
+            .into_msg(&deps.querier, Addr::unchecked(recipient))
+        })
+        .collect()
+}
+
+/// Validates the referrer of a message, senders cannot refer themselves
+fn validate_referrer(
+    api: &dyn Api,
+    sender: &Addr,
+    referrer: Option<String>,
+) -> Result<Option<Addr>, ContractError> {
+    match referrer {
+        Some(referrer) => {
+            let referrer = addr_validate_to_lower(api, &referrer)?;
+            if &referrer == sender {
+                return Err(ContractError::InvalidReferrer {});
+            }
+            Ok(Some(referrer))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Share of the platform fees owed to the referrer, which the fee collectors do not receive
+fn referral_share(config: &Config, referrer: Option<&Addr>, amount: Uint128) -> Uint128 {
+    match referrer {
+        Some(_) => amount.multiply_ratio(config.referral_fee_share as u128, HUNDRED_PERCENT),
+        None => Uint128::zero(),
+    }
+}
+
+/// Credits the referrer with its share of the platform fees and returns the share. The share is
+/// kept by the proxy until claimed
+fn credit_referral_fees(
+    storage: &mut dyn Storage,
+    config: &Config,
+    referrer: Option<&Addr>,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    let share = referral_share(config, referrer, amount);
+    if let Some(referrer) = referrer {
+        credit_referrer(storage, referrer, asset_info, share)?;
+    }
+    Ok(share)
+}
+
+/// Adds an amount to the claimable and earned referral rewards of the referrer
+fn credit_referrer(
+    storage: &mut dyn Storage,
+    referrer: &Addr,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let mut rewards = REFERRAL_REWARDS
+        .may_load(storage, referrer.as_str())?
+        .unwrap_or_default();
+    add_to_assets(&mut rewards.claimable, asset_info, amount);
+    add_to_assets(&mut rewards.total_earned, asset_info, amount);
+    REFERRAL_REWARDS.save(storage, referrer.as_str(), &rewards)
+}
+
+/// Messages pulling the referrer's share of the platform fees from the payer to the proxy.
+/// Native fees are attached to the message and already held by the proxy
+fn collect_referral_fees(
+    env: &Env,
+    asset_info: &AssetInfo,
+    payer: String,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    match asset_info {
+        AssetInfo::Token { contract_addr } if !amount.is_zero() => {
+            Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: payer,
+                    recipient: env.contract.address.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            })])
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+/// Adds an amount to the entry of its asset, the entry is created when missing
+fn add_to_assets(assets: &mut Vec<Asset>, info: &AssetInfo, amount: Uint128) {
+    match assets.iter_mut().find(|asset| &asset.info == info) {
+        Some(asset) => asset.amount += amount,
+        None => assets.push(Asset {
+            info: info.clone(),
+            amount,
+        }),
+    }
+}
+
+/// Sends the referral rewards credited to the sender
+fn claim_referral_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut rewards = REFERRAL_REWARDS
+        .may_load(deps.storage, info.sender.as_str())?
+        .unwrap_or_default();
+    let claimable: Vec<Asset> = rewards
+        .claimable
+        .drain(..)
+        .filter(|asset| !asset.amount.is_zero())
+        .collect();
+    if claimable.is_empty() {
+        return Err(ContractError::NoReferralRewards {});
+    }
+    REFERRAL_REWARDS.save(deps.storage, info.sender.as_str(), &rewards)?;
+
+    let mut resp = Response::new().add_attribute("action", "claim_referral_rewards");
+    for asset in claimable {
+        resp = resp.add_attribute("claimed", asset.to_string());
+        resp = resp.add_message(asset.into_msg(&deps.querier, info.sender.clone())?);
+    }
+    Ok(resp)
+}
+
+/// Stakes the LP tokens a pair bond minted to the proxy in the generator. The proxy is the
+/// beneficiary of the deposit since the generator only pays the rewards to it
+fn stake_protocol_liquidity(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    events: &[Event],
+) -> Result<Vec<SubMsg>, ContractError> {
+    let amount = reply_event_amount(events, &config.pool_pair_address, "share")?;
+    let generator_address = match &config.generator_address {
+        Some(generator_address) => generator_address,
+        // The generator was removed while the request was pending
+        None => {
+            return Ok(vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: config.liquidity_token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: config.pair_lp_tokens_holder.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            })])
         }
+    };
+    PROTOCOL_LIQUIDITY.update(
+        deps.storage,
+        config.liquidity_token.as_str(),
+        |staked| -> StdResult<_> { Ok(staked.unwrap_or_default() + amount) },
+    )?;
+
+    // The deposit pays out the rewards accrued so far
+    let rewards = protocol_liquidity_reward_messages(
+        deps.as_ref(),
+        env,
+        generator_address,
+        &config.liquidity_token,
+        &config.pair_lp_tokens_holder,
+    )?;
+    let deposit = WasmMsg::Execute {
+        contract_addr: config.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: generator_address.to_string(),
+            amount,
+            msg: to_binary(&GeneratorCw20HookMsg::DepositFor(
+                env.contract.address.clone(),
+            ))?,
+        })?,
+        funds: vec![],
+    };
+    let mut messages = vec![generator_submessage(
+        deps.storage,
+        &deps.querier,
+        env,
+        generator_address,
+        &config.liquidity_token,
+        &config.pair_lp_tokens_holder,
+        deposit,
+    )?];
+    messages.extend(rewards.into_iter().map(SubMsg::new));
+    Ok(messages)
+}
+
+/// Claims the generator rewards of all LP tokens staked by the proxy and sends them to the pair LP
+/// tokens holder
+fn harvest_protocol_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
     }
+    let generator_address = config
+        .generator_address
+        .ok_or(ContractError::GeneratorNotSet {})?;
 
-    let mut resp = Response::new();
+    let mut resp = Response::new().add_attribute("action", "harvest_protocol_liquidity");
+    let lp_tokens = PROTOCOL_LIQUIDITY
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| String::from_utf8(key).map_err(StdError::from))
+        .collect::<StdResult<Vec<String>>>()?;
+    for lp_token in lp_tokens {
+        let lp_token = Addr::unchecked(lp_token);
+        let rewards = protocol_liquidity_reward_messages(
+            deps.as_ref(),
+            &env,
+            &generator_address,
+            &lp_token,
+            &config.pair_lp_tokens_holder,
+        )?;
+        // Withdrawing nothing only claims the rewards
+        let claim = WasmMsg::Execute {
+            contract_addr: generator_address.to_string(),
+            msg: to_binary(&GeneratorExecuteMsg::Withdraw {
+                lp_token: lp_token.clone(),
+                amount: Uint128::zero(),
+            })?,
+            funds: vec![],
+        };
+        resp = resp
+            .add_submessage(generator_submessage(
+                deps.storage,
+                &deps.querier,
+                &env,
+                &generator_address,
+                &lp_token,
+                &config.pair_lp_tokens_holder,
+                claim,
+            )?)
+            .add_messages(rewards);
+    }
+    Ok(resp)
+}
 
+/// Withdraws LP tokens staked by the proxy from the generator and sends them along with their
+/// rewards to the pair LP tokens holder
+fn unstake_protocol_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let mut fury_equiv_for_ust =
-        get_fury_equivalent_to_ust(deps.as_ref(), ust_amount_provided).unwrap();
-    let fury_pre_discount;
-    let funds_owner;
-    let bonding_period;
-    let mut discounted_rate = 10000u16; // 100 percent
-    if is_fury_provided {
-        if fury_equiv_for_ust > fury_amount_provided {
-            fury_equiv_for_ust = fury_amount_provided;
-        }
-        fury_pre_discount = Uint128::from(2u128) * fury_equiv_for_ust;
-        discounted_rate -= config.pair_discount_rate;
-        funds_owner = config.pair_fury_reward_wallet.to_string();
-        bonding_period = config.pair_bonding_period_in_sec;
-    } else {
-        fury_pre_discount = fury_equiv_for_ust;
-        discounted_rate -= config.native_discount_rate;
-        funds_owner = config.native_investment_reward_wallet.to_string();
-        bonding_period = config.native_bonding_period_in_sec;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
     }
-    let total_fury_amount = fury_pre_discount
-        .checked_mul(Uint128::from(10000u128))
-        .unwrap_or_default()
-        .checked_div(Uint128::from(discounted_rate))
+    let generator_address = config
+        .generator_address
+        .ok_or(ContractError::GeneratorNotSet {})?;
+    let lp_token = addr_validate_to_lower(deps.api, &lp_token)?;
+    let staked = PROTOCOL_LIQUIDITY
+        .may_load(deps.storage, lp_token.as_str())?
         .unwrap_or_default();
+    if amount > staked {
+        return Err(ContractError::InsufficientProtocolLiquidity { staked });
+    }
+    if amount == staked {
+        PROTOCOL_LIQUIDITY.remove(deps.storage, lp_token.as_str());
+    } else {
+        PROTOCOL_LIQUIDITY.save(deps.storage, lp_token.as_str(), &(staked - amount))?;
+    }
 
-    // Get the existing bonded_rewards_details for this user
-    let mut bonded_rewards_details = Vec::new();
-    let all_bonded_rewards_details =
-        BONDED_REWARDS_DETAILS.may_load(deps.storage, user_address.to_string())?;
-    match all_bonded_rewards_details {
-        Some(some_bonded_rewards_details) => {
-            bonded_rewards_details = some_bonded_rewards_details;
-        }
-        None => {}
-    }
-	let bonds_for_user = bonded_rewards_details.len() as u64;
-	if config.max_bonding_limit_per_user <= bonds_for_user {
-		println!("bonds for this user = {:?}", bonds_for_user);
-		return Err(ContractError::Std(StdError::GenericErr {
-			msg: String::from("Too many bonded rewards for this user"),
-		}));
-	}
+    let rewards = protocol_liquidity_reward_messages(
+        deps.as_ref(),
+        &env,
+        &generator_address,
+        &lp_token,
+        &config.pair_lp_tokens_holder,
+    )?;
+    let withdraw = WasmMsg::Execute {
+        contract_addr: generator_address.to_string(),
+        msg: to_binary(&GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_token.clone(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+    Ok(Response::new()
+        .add_submessage(generator_submessage(
+            deps.storage,
+            &deps.querier,
+            &env,
+            &generator_address,
+            &lp_token,
+            &config.pair_lp_tokens_holder,
+            withdraw,
+        )?)
+        .add_message(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: config.pair_lp_tokens_holder.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })
+        .add_messages(rewards)
+        .add_attribute("action", "unstake_protocol_liquidity")
+        .add_attribute("amount", amount))
+}
 
-    let mut bonding_start_timestamp = Timestamp::from_seconds(0u64);
-    if config.swap_opening_date < env.block.time {
-        bonding_start_timestamp = env.block.time;
+/// Wraps a generator call so that the proxy rewards it pays the proxy for the LP token are
+/// forwarded to the recipient on reply. They are measured from the balance of the proxy in the
+/// proxy reward token of the pool, pools without one need no reply
+#[allow(clippy::too_many_arguments)]
+fn generator_submessage(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    generator_address: &Addr,
+    lp_token: &Addr,
+    recipient: &Addr,
+    msg: WasmMsg,
+) -> StdResult<SubMsg> {
+    let reward_info: RewardInfoResponse = querier.query_wasm_smart(
+        generator_address.to_string(),
+        &GeneratorQueryMsg::RewardInfo {
+            lp_token: lp_token.clone(),
+        },
+    )?;
+    let reward_token = match reward_info.proxy_reward_token {
+        Some(reward_token) => reward_token,
+        None => return Ok(SubMsg::new(msg)),
+    };
+    let prev_balance =
+        query_token_balance(querier, reward_token.clone(), env.contract.address.clone())?;
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(storage, &sub_req_id)?;
+    }
+    SUB_MESSAGE_DETAILS.save(
+        storage,
+        sub_req_id.to_string(),
+        &SubMessageDetails {
+            sub_req_id: sub_req_id.to_string(),
+            request_type: SubMessageType::GeneratorSubMsg,
+            next_action: SubMessageNextAction::ForwardProxyRewards,
+            sub_message_payload: to_binary(&ProxyRewardDetails {
+                recipient: recipient.to_string(),
+                reward_token: reward_token.to_string(),
+                prev_balance,
+            })?,
+            funds: vec![],
+            user_address: env.contract.address.to_string(),
+            is_fury_provided: false,
+            term_id: None,
+            request_id: 0,
+        },
+    )?;
+    Ok(SubMsg::reply_on_success(msg, sub_req_id))
+}
+
+/// Forwards the proxy rewards the generator call paid the proxy
+fn forward_proxy_rewards(
+    deps: Deps,
+    env: Env,
+    details: ProxyRewardDetails,
+) -> Result<Response, ContractError> {
+    let balance = query_token_balance(
+        &deps.querier,
+        Addr::unchecked(&details.reward_token),
+        env.contract.address,
+    )?;
+    let reward = balance.saturating_sub(details.prev_balance);
+    let mut resp = Response::new().add_attribute("proxy_reward", reward);
+    if !reward.is_zero() {
+        resp = resp.add_message(WasmMsg::Execute {
+            contract_addr: details.reward_token,
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: details.recipient,
+                amount: reward,
+            })?,
+            funds: vec![],
+        });
     }
+    Ok(resp)
+}
 
-    bonded_rewards_details.push(BondedRewardsDetails {
-        user_address: user_address.to_string(),
-        bonded_amount: total_fury_amount,
-        bonding_period: bonding_period,
-        bonding_start_timestamp: bonding_start_timestamp,
-    });
-    BONDED_REWARDS_DETAILS.save(
+/// Messages forwarding the ASTRO rewards the generator pays the proxy for its stake of the LP
+/// token to the recipient. They must follow the generator message paying the rewards, whose proxy
+/// rewards are forwarded on reply
+fn protocol_liquidity_reward_messages(
+    deps: Deps,
+    env: &Env,
+    generator_address: &Addr,
+    lp_token: &Addr,
+    recipient: &Addr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let pending: PendingTokenResponse = deps.querier.query_wasm_smart(
+        generator_address.to_string(),
+        &GeneratorQueryMsg::PendingToken {
+            lp_token: lp_token.clone(),
+            user: env.contract.address.clone(),
+        },
+    )?;
+    let reward_info: RewardInfoResponse = deps.querier.query_wasm_smart(
+        generator_address.to_string(),
+        &GeneratorQueryMsg::RewardInfo {
+            lp_token: lp_token.clone(),
+        },
+    )?;
+
+    if pending.pending.is_zero() {
+        return Ok(vec![]);
+    }
+    Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: reward_info.base_reward_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: pending.pending,
+        })?,
+        funds: vec![],
+    })])
+}
+
+/// Sends the platform fees to the collector wallet and the claimed Fury to the receiver.
+/// Fees paid in Fury are taken out of the claimed amount
+fn transfer_claimed_rewards(
+    deps: Deps,
+    config: Config,
+    payer: String,
+    platform_fees: Uint128,
+    fury_fees: Uint128,
+    receiver: String,
+    withdrawal_amount: Uint128,
+) -> Result<Response, ContractError> {
+    if fury_fees >= withdrawal_amount && !fury_fees.is_zero() {
+        return Err(ContractError::FuryFeesExceedAmount {
+            fury_fees,
+            amount: withdrawal_amount,
+        });
+    }
+    let mut rsp = Response::new();
+    //Send the platform fees to platform fee collector wallet
+    rsp = rsp.add_messages(collect_platform_fees(deps, &config, payer, platform_fees)?);
+    rsp = rsp.add_messages(distribute_fees(
+        deps,
+        &config,
+        &fury_asset_info(&config),
+        fury_fees,
+    )?);
+    let transfer_msg = Cw20ExecuteMsg::Transfer {
+        recipient: receiver,
+        amount: withdrawal_amount - fury_fees,
+    };
+    let exec = WasmMsg::Execute {
+        contract_addr: config.custom_token_address.to_string(),
+        msg: to_binary(&transfer_msg).unwrap(),
+        funds: vec![],
+    };
+    let send: SubMsg = SubMsg::new(exec);
+    let data_msg = format!("Amount {} transferred", withdrawal_amount).into_bytes();
+
+    Ok(rsp
+        .add_submessage(send)
+        .add_attribute("withdrawn", withdrawal_amount.to_string())
+        .set_data(data_msg))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    pay_fees_in_fury: bool,
+    deduct_fees_from_proceeds: bool,
+    pair_id: Option<String>,
+    referrer: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    // Check if the swap_enable_date is passed
+    if config.swap_opening_date.nanos() > env.block.time.nanos() {
+        if config.early_access_window_in_sec == 0 {
+            //return error
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Swap is not enabled yet!!!",
+            ))));
+        }
+        // Early access is counted in the quote asset
+        let offer_value = if is_fury(&config, &offer_asset.info) {
+            get_ust_equivalent_to_fury(deps.as_ref(), &config, offer_asset.amount)?
+        } else {
+            offer_asset.amount
+        };
+        use_early_access(deps.storage, &env, &config, &info.sender, offer_value)?;
+    }
+    apply_swap_limits(
+        deps.branch(),
+        &env,
+        &config,
+        info.sender.as_str(),
+        &offer_asset,
+    )?;
+    // Swap is enabled so proceed
+    // Check if platform fees is provided
+    let required_ust_fees: Uint128;
+    required_ust_fees = query_platform_fees(
+        deps.as_ref(),
+        env.clone(),
+        to_binary(&ExecuteMsg::Swap {
+            offer_asset: offer_asset.clone(),
+            belief_price: belief_price.clone(),
+            max_spread: max_spread.clone(),
+            to: Some(to.clone().unwrap().into_string()),
+            pay_fees_in_fury: Some(pay_fees_in_fury),
+            deduct_fees_from_proceeds: Some(deduct_fees_from_proceeds),
+            pair_id: pair_id.clone(),
+            referrer: referrer.as_ref().map(|referrer| referrer.to_string()),
+        })?,
+        Some(info.sender.to_string()),
+    )?;
+    // Fees deducted from the proceeds are not attached
+    let (platform_fees, fury_fees) = if deduct_fees_from_proceeds {
+        (Uint128::zero(), Uint128::zero())
+    } else {
+        platform_fees_by_asset(deps.as_ref(), &config, required_ust_fees, pay_fees_in_fury)?
+    };
+    let mut fees = received_platform_fees(&config, &info.funds, platform_fees);
+    if offer_asset.info == config.quote_asset && offer_asset.is_native_token() {
+        fees = fees.checked_sub(offer_asset.amount).unwrap();
+        let native_tax = offer_asset.compute_tax(&deps.querier)?;
+        fees = fees.checked_sub(native_tax).unwrap();
+    }
+    if fees < platform_fees {
+        return Err(ContractError::InsufficientFees {
+            required: platform_fees,
+            received: fees,
+        });
+    }
+    //Platform fees provided is good
+    //If offer asset is custom token (sell fury),
+    // transfer the asset amount of fury tokens to this contract
+    let to_address: Option<String>;
+    match to {
+        Some(to_addr) => to_address = Some(to_addr.into_string()),
+        None => to_address = Some(info.sender.clone().into_string()),
+    }
+    //Remove platform fees from funds and transfer it to platform_fees_collector_wallet
+    let mut funds_to_send = vec![];
+    if offer_asset.is_native_token() {
+        if let AssetInfo::NativeToken { denom, .. } = &offer_asset.info {
+            funds_to_send = vec![Coin {
+                denom: denom.to_string(),
+                amount: offer_asset.amount,
+            }];
+        }
+    }
+    if deduct_fees_from_proceeds {
+        return swap_deducting_fees_from_proceeds(
+            deps,
+            env,
+            info.sender.to_string(),
+            false,
+            offer_asset,
+            belief_price,
+            max_spread,
+            to_address.unwrap(),
+            required_ust_fees,
+            funds_to_send,
+            pair_id,
+            referrer,
+        );
+    }
+    // The referrer's share is kept by the proxy, the fee collectors get the rest
+    let referral_fees = credit_referral_fees(
+        deps.storage,
+        &config,
+        referrer.as_ref(),
+        &config.quote_asset,
+        platform_fees,
+    )?;
+    let referral_fury_fees = credit_referral_fees(
         deps.storage,
-        user_address.to_string(),
-        &bonded_rewards_details,
+        &config,
+        referrer.as_ref(),
+        &fury_asset_info(&config),
+        fury_fees,
+    )?;
+    let mut referral_msgs = collect_referral_fees(
+        &env,
+        &config.quote_asset,
+        info.sender.to_string(),
+        referral_fees,
     )?;
+    referral_msgs.extend(collect_referral_fees(
+        &env,
+        &fury_asset_info(&config),
+        info.sender.to_string(),
+        referral_fury_fees,
+    )?);
+    let platform_fees = platform_fees - referral_fees;
+    let fury_fees = fury_fees - referral_fury_fees;
+    if let AssetInfo::Token { contract_addr } = &offer_asset.info {
+        return Ok(forward_swap_to_astro(
+            deps,
+            env,
+            info,
+            contract_addr.to_string(),
+            Cw20HookMsg::Swap {
+                belief_price: belief_price,
+                max_spread: max_spread,
+                to: to_address,
+            },
+            offer_asset.amount,
+            funds_to_send,
+            platform_fees,
+            fury_fees,
+            pair_id,
+        )?
+        .add_messages(referral_msgs));
+    }
+    //Check if assets provided are native tokens
+    offer_asset.info.check(deps.api)?;
+    let swap_msg = PairExecuteMsg::Swap {
+        offer_asset: offer_asset,
+        belief_price: belief_price,
+        max_spread: max_spread,
+        to: to_address,
+    };
+    let exec = WasmMsg::Execute {
+        contract_addr: config.pool_pair_address.to_string(),
+        msg: to_binary(&swap_msg).unwrap(),
+        funds: funds_to_send,
+    };
+    let mut send: SubMsg = SubMsg::new(exec);
+    let mut sub_req_id = 1;
+    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
+        req_id += 1;
+        SUB_REQ_ID.save(deps.storage, &req_id)?;
+        sub_req_id = req_id;
+    } else {
+        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    }
+    send.id = sub_req_id;
+    send.reply_on = ReplyOn::Always;
 
-    // Prepare submessage for Execute transfer_from funds_owner to proxy contract
-    let transfer_from_msg = Cw20ExecuteMsg::TransferFrom {
-        owner: funds_owner.clone(),
-        recipient: env.contract.address.into_string(),
-        amount: total_fury_amount,
+    let mut resp = Response::new();
+    resp = resp.add_submessage(send);
+    let data_msg = format!("Swapping {:?}", swap_msg).into_bytes();
+
+    //Add message to transfer platform fees to platform fee collector wallet
+    resp = resp.add_messages(collect_platform_fees(
+        deps.as_ref(),
+        &config,
+        info.sender.to_string(),
+        platform_fees,
+    )?);
+    resp = resp.add_messages(collect_fury_fees(
+        &config,
+        info.sender.to_string(),
+        fury_fees,
+    )?);
+    resp = resp.add_messages(referral_msgs);
+
+    Ok(resp
+        .add_attribute("action", "Sending swap message")
+        .set_data(data_msg))
+}
+
+/// Swaps with the proxy as the recipient of the returned asset, the platform fees are taken out
+/// of it on reply and the rest is forwarded to the receiver. A CW20 offer is pulled from the
+/// sender unless the proxy already holds it
+#[allow(clippy::too_many_arguments)]
+fn swap_deducting_fees_from_proceeds(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    offer_held: bool,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    receiver: String,
+    required_fees: Uint128,
+    funds_to_send: Vec<Coin>,
+    pair_id: Option<String>,
+    referrer: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    let (ask_asset_info, platform_fees) = if is_fury(&config, &offer_asset.info) {
+        (config.quote_asset.clone(), required_fees)
+    } else {
+        (
+            fury_asset_info(&config),
+            fury_platform_fees(deps.as_ref(), &config, required_fees)?,
+        )
     };
-    let exec_transfer_from = WasmMsg::Execute {
-        contract_addr: config.custom_token_address.to_string(),
-        msg: to_binary(&transfer_from_msg).unwrap(),
-        funds: vec![],
+
+    let mut resp = Response::new();
+    // The pair checks the gross return, the net amount is checked again on reply
+    let exec = match &offer_asset.info {
+        AssetInfo::Token { contract_addr } => {
+            if !offer_held {
+                resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: sender.clone(),
+                        recipient: env.contract.address.to_string(),
+                        amount: offer_asset.amount,
+                    })?,
+                    funds: vec![],
+                }));
+            }
+            WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: config.pool_pair_address.clone(),
+                    amount: offer_asset.amount,
+                    msg: to_binary(&Cw20HookMsg::Swap {
+                        belief_price,
+                        max_spread,
+                        to: Some(env.contract.address.to_string()),
+                    })?,
+                })?,
+                funds: vec![],
+            }
+        }
+        AssetInfo::NativeToken { .. } => WasmMsg::Execute {
+            contract_addr: config.pool_pair_address.clone(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                belief_price,
+                max_spread,
+                to: Some(env.contract.address.to_string()),
+            })?,
+            funds: funds_to_send,
+        },
     };
-    let mut send_transfer_from: SubMsg = SubMsg::new(exec_transfer_from);
     let mut sub_req_id = 1;
     if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
         req_id += 1;
@@ -759,105 +3985,243 @@ pub fn transfer_custom_assets_from_funds_owner_to_proxy(
     } else {
         SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
     }
-    send_transfer_from.reply_on = ReplyOn::Always;
-    send_transfer_from.id = sub_req_id;
-    resp = resp.add_submessage(send_transfer_from);
-    let pl_msg = PairExecuteMsg::ProvideLiquidity {
-        assets: assets,
-        slippage_tolerance: slippage_tolerance,
-        auto_stake: auto_stake,
-        receiver: receiver,
-    };
+    SUB_MESSAGE_DETAILS.save(
+        deps.storage,
+        sub_req_id.to_string(),
+        &SubMessageDetails {
+            sub_req_id: sub_req_id.to_string(),
+            request_type: SubMessageType::SwapSubMsg,
+            next_action: SubMessageNextAction::ForwardSwapProceeds,
+            sub_message_payload: to_binary(&SwapProceedsDetails {
+                receiver,
+                offer_amount: offer_asset.amount,
+                ask_asset_info,
+                platform_fees,
+                belief_price,
+                max_spread,
+                pair_id,
+                referrer,
+            })?,
+            funds: vec![],
+            user_address: sender,
+            is_fury_provided: is_fury(&config, &offer_asset.info),
+            term_id: None,
+            request_id: 0,
+        },
+    )?;
 
-    // Save the submessage_payload
-    if is_fury_provided {
-        SUB_MESSAGE_DETAILS.save(
-            deps.storage,
-            sub_req_id.to_string(),
-            &SubMessageDetails {
-                sub_req_id: sub_req_id.to_string(),
-                request_type: SubMessageType::ProvideLiquiditySubMsg,
-                next_action: SubMessageNextAction::IncreaseAllowance,
-                sub_message_payload: to_binary(&pl_msg)?,
-                funds: funds,
-                user_address: user_address,
-                is_fury_provided: is_fury_provided,
-            },
-        )?;
-    } else {
-        // Save the submessage_payload
-        SUB_MESSAGE_DETAILS.save(
-            deps.storage,
-            sub_req_id.to_string(),
-            &SubMessageDetails {
-                sub_req_id: sub_req_id.to_string(),
-                request_type: SubMessageType::ProvideLiquiditySubMsg,
-                next_action: SubMessageNextAction::TransferToNativeInvestmentReceiveWallet,
-                sub_message_payload: to_binary(&pl_msg)?,
-                funds: funds,
-                user_address: user_address,
-                is_fury_provided: is_fury_provided,
-            },
-        )?;
+    Ok(resp
+        .add_submessage(SubMsg::reply_on_success(exec, sub_req_id))
+        .add_attribute(
+            "action",
+            "Sending swap message with fees deducted from proceeds",
+        ))
+}
+
+/// Takes the platform fees out of the asset returned by a swap and forwards the rest to the
+/// receiver. belief_price and max_spread are checked against the amount left after fees
+fn forward_swap_proceeds(
+    deps: DepsMut,
+    details: SwapProceedsDetails,
+    events: &[Event],
+) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage, details.pair_id.as_deref())?;
+    let pair = config.pool_pair_address.as_str();
+    let return_amount = reply_event_amount(events, pair, "return_amount")?;
+    let tax_amount = reply_event_amount(events, pair, "tax_amount")?;
+    let spread_amount = reply_event_amount(events, pair, "spread_amount")?;
+    let proceeds = return_amount
+        .checked_sub(tax_amount)
+        .map_err(StdError::from)?;
+    if details.platform_fees >= proceeds && !details.platform_fees.is_zero() {
+        return Err(ContractError::FeesExceedProceeds {
+            fees: details.platform_fees,
+            proceeds,
+        });
     }
-    match platform_fees {
-        Some(pf) => {
-            let pf_asset = Asset {
-                info: AssetInfo::NativeToken {
-                    denom: String::from("uusd"),
-                },
-                amount: pf.amount,
-            };
-            let taxed_platform_fees = pf_asset.deduct_tax(&deps.querier)?;
-            resp = resp.add_message(CosmosMsg::Bank(BankMsg::Send {
-                to_address: config.platform_fees_collector_wallet.into_string(),
-                amount: vec![taxed_platform_fees],
-            }));
+    let net_amount = proceeds - details.platform_fees;
+
+    if let Some(max_spread) = details.max_spread {
+        if let Some(belief_price) = details.belief_price {
+            if belief_price.is_zero() {
+                return Err(ContractError::MaxSpreadAssertion {});
+            }
+            let expected_return = details
+                .offer_amount
+                .multiply_ratio(belief_price.denominator(), belief_price.numerator());
+            if net_amount < expected_return
+                && Decimal::from_ratio(expected_return - net_amount, expected_return) > max_spread
+            {
+                return Err(ContractError::MaxSpreadAssertion {});
+            }
+        } else {
+            let net_spread = spread_amount + (return_amount - net_amount);
+            if Decimal::from_ratio(net_spread, return_amount + spread_amount) > max_spread {
+                return Err(ContractError::MaxSpreadAssertion {});
+            }
         }
-        None => {}
-    };
+    }
 
-    Ok(resp.add_attribute(
-        "action",
-        "Transferring fury from treasury funds owner to proxy",
-    ))
+    let forward_msg = Asset {
+        info: details.ask_asset_info.clone(),
+        amount: net_amount,
+    }
+    .into_msg(&deps.querier, Addr::unchecked(details.receiver))?;
+    // The fees are already held by the proxy, the referrer's share stays there
+    let referral_fees = credit_referral_fees(
+        deps.storage,
+        &config,
+        details.referrer.as_ref(),
+        &details.ask_asset_info,
+        details.platform_fees,
+    )?;
+    Ok(Response::new()
+        .add_message(forward_msg)
+        .add_messages(distribute_fees(
+            deps.as_ref(),
+            &config,
+            &details.ask_asset_info,
+            details.platform_fees - referral_fees,
+        )?)
+        .add_attribute("action", "Forwarding swap proceeds")
+        .add_attribute("net_amount", net_amount.to_string())
+        .add_attribute("platform_fees", details.platform_fees.to_string()))
 }
 
-pub fn provide_liquidity(
+/// Reads an attribute the pair emitted during a submessage. Only the wasm event of the pair is
+/// read, so that attributes of other contracts called during the submessage cannot stand in
+fn pair_event_attribute<'a>(events: &'a [Event], pair: &str, key: &str) -> StdResult<&'a str> {
+    events
+        .iter()
+        .filter(|event| {
+            event.ty == "wasm"
+                && event.attributes.iter().any(|attribute| {
+                    attribute.key == "_contract_address" && attribute.value == pair
+                })
+        })
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.as_str())
+        .ok_or_else(|| StdError::generic_err(format!("{} missing in reply", key)))
+}
+
+/// Reads an amount the pair emitted during a submessage
+fn reply_event_amount(events: &[Event], pair: &str, key: &str) -> StdResult<Uint128> {
+    pair_event_attribute(events, pair, key)?
+        .parse::<u128>()
+        .map(Uint128::from)
+        .map_err(|_| StdError::generic_err(format!("Invalid {} in reply", key)))
+}
+
+/// Runs all operations but the Fury hop through the router with the proxy as recipient, the
+/// Fury hop is made by the proxy on reply as the Fury pair only accepts swaps from the proxy
+#[allow(clippy::too_many_arguments)]
+fn execute_swap_operations(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    assets: [Asset; 2],
-    slippage_tolerance: Option<Decimal>,
-    auto_stake: Option<bool>,
-    receiver: Option<String>,
-    next_action: SubMessageNextAction,
-    platform_fee_funds: Option<Coin>,
+    offer_asset: Asset,
+    operations: Vec<SwapOperation>,
+    minimum_receive: Uint128,
+    to: Option<String>,
+    pair_id: Option<String>,
 ) -> Result<Response, ContractError> {
-    let mut resp = Response::new();
-    let config: Config = CONFIG.load(deps.storage)?;
-    // Get the amount of Fury tokens to be specified in transfer_from and increase_allowance
-    let mut amount = Uint128::zero();
-    if !assets[0].info.is_native_token() {
-        amount = assets[0].amount;
-    } else if !assets[1].info.is_native_token() {
-        amount = assets[1].amount;
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
+    let router_address = config
+        .router_address
+        .clone()
+        .ok_or(ContractError::RouterNotConfigured {})?;
+    // Early access is used on reply, once the quote asset value of the route is known
+    if config.swap_opening_date.nanos() > env.block.time.nanos()
+        && config.early_access_window_in_sec == 0
+    {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Swap is not enabled yet!!!",
+        ))));
     }
+    offer_asset.info.check(deps.api)?;
+    let receiver = match to {
+        Some(to_addr) => addr_validate_to_lower(deps.api, &to_addr)?.to_string(),
+        None => info.sender.to_string(),
+    };
 
-    let user_address = info.sender.into_string();
-
-    // Prepare submessage for Execute transfer_from user wallet to proxy contract
-    let transfer_from_msg = Cw20ExecuteMsg::TransferFrom {
-        owner: user_address.clone(),
-        recipient: env.contract.address.into_string(),
-        amount: amount,
+    let fury_hop = SwapOperation::AstroSwap {
+        offer_asset_info: config.quote_asset.clone(),
+        ask_asset_info: fury_asset_info(&config),
     };
-    let exec_transfer_from = WasmMsg::Execute {
-        contract_addr: config.custom_token_address.to_string(),
-        msg: to_binary(&transfer_from_msg).unwrap(),
-        funds: vec![],
+    let route = match operations.split_last() {
+        Some((last, route)) if *last == fury_hop && !route.is_empty() => route.to_vec(),
+        _ => return Err(ContractError::InvalidSwapRoute {}),
+    };
+    let touches_fury = route.iter().any(|operation| match operation {
+        SwapOperation::AstroSwap {
+            offer_asset_info,
+            ask_asset_info,
+        } => is_fury(&config, offer_asset_info) || is_fury(&config, ask_asset_info),
+        SwapOperation::NativeSwap { .. } => false,
+    });
+    if touches_fury
+        || offer_asset.info == config.quote_asset
+        || route[route.len() - 1].get_target_asset_info() != config.quote_asset
+    {
+        return Err(ContractError::InvalidSwapRoute {});
+    }
+    // The router runs without a minimum, the minimum receive bounds the whole route on the
+    // Fury hop
+    if minimum_receive.is_zero() {
+        return Err(ContractError::MinimumReceiveRequired {});
+    }
+    // Funds other than the native offer would be left in the proxy
+    let unexpected_funds = info.funds.iter().any(|coin| match &offer_asset.info {
+        AssetInfo::NativeToken { denom } => coin.denom != *denom && !coin.amount.is_zero(),
+        AssetInfo::Token { .. } => !coin.amount.is_zero(),
+    });
+    if unexpected_funds {
+        return Err(ContractError::UnexpectedFunds {});
+    }
+
+    let prev_balance = config
+        .quote_asset
+        .query_pool(&deps.querier, env.contract.address.clone())?;
+    let mut resp = Response::new();
+    let exec = match &offer_asset.info {
+        AssetInfo::Token { contract_addr } => {
+            resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: offer_asset.amount,
+                })?,
+                funds: vec![],
+            }));
+            WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: router_address.to_string(),
+                    amount: offer_asset.amount,
+                    msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                        operations: route,
+                        minimum_receive: None,
+                        to: Some(env.contract.address.to_string()),
+                    })?,
+                })?,
+                funds: vec![],
+            }
+        }
+        AssetInfo::NativeToken { .. } => {
+            offer_asset.assert_sent_native_token_balance(&info)?;
+            WasmMsg::Execute {
+                contract_addr: router_address.to_string(),
+                msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: route,
+                    minimum_receive: None,
+                    to: Some(env.contract.address.clone()),
+                })?,
+                funds: vec![offer_asset.deduct_tax(&deps.querier)?],
+            }
+        }
     };
-    let mut send_transfer_from: SubMsg = SubMsg::new(exec_transfer_from);
     let mut sub_req_id = 1;
     if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
         req_id += 1;
@@ -866,386 +4230,596 @@ pub fn provide_liquidity(
     } else {
         SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
     }
-    send_transfer_from.reply_on = ReplyOn::Always;
-    send_transfer_from.id = sub_req_id;
-    resp = resp.add_submessage(send_transfer_from);
-    let pl_msg = PairExecuteMsg::ProvideLiquidity {
-        assets: assets,
-        slippage_tolerance: slippage_tolerance,
-        auto_stake: auto_stake,
-        receiver: receiver,
-    };
-    // Save the submessage_payload
     SUB_MESSAGE_DETAILS.save(
         deps.storage,
         sub_req_id.to_string(),
         &SubMessageDetails {
             sub_req_id: sub_req_id.to_string(),
-            request_type: SubMessageType::ProvideLiquiditySubMsg,
-            next_action: next_action,
-            sub_message_payload: to_binary(&pl_msg)?,
-            funds: info.funds,
-            user_address: user_address.clone(),
-            is_fury_provided: FURY_PROVIDED,
+            request_type: SubMessageType::RouteSubMsg,
+            next_action: SubMessageNextAction::SwapToFury,
+            sub_message_payload: to_binary(&RouteSwapDetails {
+                receiver,
+                prev_balance,
+                minimum_receive,
+                pair_id,
+            })?,
+            funds: vec![],
+            user_address: info.sender.to_string(),
+            is_fury_provided: false,
+            term_id: None,
+            request_id: 0,
         },
     )?;
-    if let Some(platform_fees) = platform_fee_funds {
-        if platform_fees.amount > Uint128::zero() {
-            let pf_asset = Asset {
-                info: AssetInfo::NativeToken {
-                    denom: String::from("uusd"),
-                },
-                amount: platform_fees.amount,
-            };
-            let taxed_platform_fees = pf_asset.deduct_tax(&deps.querier)?;
-            resp = resp.add_message(CosmosMsg::Bank(BankMsg::Send {
-                to_address: config.platform_fees_collector_wallet.into_string(),
-                amount: vec![taxed_platform_fees],
-            }));
-        }
-    }
-    Ok(resp.add_attribute("action", "Transferring tokens for Provide Liquidity"))
+
+    Ok(resp
+        .add_submessage(SubMsg::reply_on_success(exec, sub_req_id))
+        .add_attribute("action", "Sending swap operations to router"))
 }
 
-pub fn withdraw_liquidity(
-    deps: DepsMut,
+/// Takes the platform fees out of the quote asset returned by the router and swaps the rest
+/// into Fury for the receiver. The minimum receive is enforced by the pair through the belief
+/// price and a zero max spread
+fn swap_route_proceeds_to_fury(
+    mut deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    received_message: Cw20ReceiveMsg,
+    details: RouteSwapDetails,
+    user_address: String,
 ) -> Result<Response, ContractError> {
-    let config: Config = CONFIG.load(deps.storage)?;
-
-    if info.sender != config.liquidity_token {
-        return Err(ContractError::Unauthorized {});
+    let config: Config = load_config(deps.storage, details.pair_id.as_deref())?;
+    let quote_received = config
+        .quote_asset
+        .query_pool(&deps.querier, env.contract.address.clone())?
+        .checked_sub(details.prev_balance)
+        .map_err(StdError::from)?;
+    if config.swap_opening_date.nanos() > env.block.time.nanos() {
+        let user = Addr::unchecked(&user_address);
+        use_early_access(deps.storage, &env, &config, &user, quote_received)?;
     }
-    let wl_msg = Cw20ExecuteMsg::Send {
-        contract: config.pool_pair_address.to_string(),
-        amount: received_message.amount,
-        msg: received_message.msg,
+    let fee_rate = config.platform_fees + config.transaction_fees + config.swap_fees;
+    let platform_fees = quote_received.multiply_ratio(fee_rate, HUNDRED_PERCENT);
+    let mut offer_asset = Asset {
+        info: config.quote_asset.clone(),
+        amount: quote_received - platform_fees,
     };
-    let exec = WasmMsg::Execute {
-        contract_addr: config.liquidity_token.to_string(),
-        msg: to_binary(&wl_msg).unwrap(),
-        funds: info.funds,
+    // The limits apply to the Fury hop, the route before it is not limited
+    apply_swap_limits(deps.branch(), &env, &config, &user_address, &offer_asset)?;
+
+    let exec = match &config.quote_asset {
+        AssetInfo::NativeToken { .. } => {
+            let offer_coin = offer_asset.deduct_tax(&deps.querier)?;
+            offer_asset.amount = offer_coin.amount;
+            WasmMsg::Execute {
+                contract_addr: config.pool_pair_address.clone(),
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: offer_asset.clone(),
+                    belief_price: Some(minimum_receive_price(
+                        offer_asset.amount,
+                        details.minimum_receive,
+                    )),
+                    max_spread: Some(Decimal::zero()),
+                    to: Some(details.receiver.clone()),
+                })?,
+                funds: vec![offer_coin],
+            }
+        }
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: config.pool_pair_address.clone(),
+                amount: offer_asset.amount,
+                msg: to_binary(&Cw20HookMsg::Swap {
+                    belief_price: Some(minimum_receive_price(
+                        offer_asset.amount,
+                        details.minimum_receive,
+                    )),
+                    max_spread: Some(Decimal::zero()),
+                    to: Some(details.receiver.clone()),
+                })?,
+            })?,
+            funds: vec![],
+        },
     };
+    Ok(Response::new()
+        .add_message(exec)
+        .add_messages(distribute_fees(
+            deps.as_ref(),
+            &config,
+            &config.quote_asset,
+            platform_fees,
+        )?)
+        .add_attribute("action", "Swapping route proceeds into Fury")
+        .add_attribute("offer_amount", offer_asset.amount.to_string())
+        .add_attribute("platform_fees", platform_fees.to_string()))
+}
 
-    let send: SubMsg = SubMsg::new(exec);
-    let resp = Response::new();
-    let data_msg = format!("Withdraw {:?}", wl_msg).into_bytes();
-    Ok(resp
-        .add_submessage(send)
-        .add_attribute("action", "Forwarding withdraw message to lptoken address")
-        .set_data(data_msg))
+/// Belief price making the pair reject a swap returning less than the minimum at a zero max spread
+fn minimum_receive_price(offer_amount: Uint128, minimum_receive: Uint128) -> Decimal {
+    Decimal::from_ratio(offer_amount, minimum_receive)
 }
 
-fn claim_investment_reward(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    receiver: String,
-    withdrawal_amount: Uint128,
-) -> Result<Response, ContractError> {
-    //Check if platform fees provided is sufficient
-    let required_ust_fees: Uint128;
-    required_ust_fees = query_platform_fees(
-        deps.as_ref(),
-        to_binary(&ExecuteMsg::RewardClaim {
-            receiver: receiver.clone(),
-            withdrawal_amount: withdrawal_amount.clone(),
-        })?,
-    )?;
-    let mut fees = Uint128::zero();
-    for fund in info.funds.clone() {
-        if fund.denom == "uusd" {
-            fees = fees.checked_add(fund.amount).unwrap();
-        }
-    }
-    if fees < required_ust_fees {
-        return Err(ContractError::InsufficientFees {
-            required: required_ust_fees,
-            received: fees,
-        });
+/// Bonds made before swap opening have a zero start timestamp and start accruing at the opening date
+fn bond_start_timestamp(config: &Config, bond: &BondedRewardsDetails) -> Timestamp {
+    if bond.bonding_start_timestamp.seconds() == Timestamp::from_seconds(0u64).seconds() {
+        config.swap_opening_date
+    } else {
+        bond.bonding_start_timestamp
     }
+}
 
-    let config = CONFIG.load(deps.storage)?;
-    let receiver_addr = deps.api.addr_validate(&receiver)?;
-    //Check if withdrawer is same as invoker
-    if receiver_addr != info.sender {
-        return Err(ContractError::Unauthorized {});
+/// Portion of the bonded amount released by the vesting schedule of the bond at the given time
+fn vested_amount(config: &Config, bond: &BondedRewardsDetails, now: Timestamp) -> Uint128 {
+    let start = bond_start_timestamp(config, bond).seconds();
+    let maturity = start + bond.bonding_period;
+    if maturity < now.seconds() {
+        return bond.bonded_amount;
     }
-    if env.block.time < config.swap_opening_date {
-        return Err(ContractError::Std(StdError::generic_err(format!(
-            "Swap Opening not reached {:?}",
-            config.swap_opening_date
-        ))));
+    let vesting_start = match bond.vesting_schedule {
+        VestingSchedule::Cliff => return Uint128::zero(),
+        VestingSchedule::Linear => start,
+        VestingSchedule::CliffThenLinear {
+            cliff_period_in_sec,
+        } => start + cliff_period_in_sec,
+    };
+    if now.seconds() < vesting_start || maturity <= vesting_start {
+        return Uint128::zero();
     }
+    bond.bonded_amount
+        .multiply_ratio(now.seconds() - vesting_start, maturity - vesting_start)
+}
 
-    let FAR_IN_FUTURE = env.block.time.plus_seconds(2000 * 24 * 60 * 60).seconds();
+/// Vested amount of the bond that has not been claimed yet
+fn claimable_amount(config: &Config, bond: &BondedRewardsDetails, now: Timestamp) -> Uint128 {
+    vested_amount(config, bond, now).saturating_sub(bond.claimed_amount)
+}
 
-    let action = "claim_investment_reward".to_string();
-    let mut unbonded_amount = Uint128::zero();
-    let mut amount_remaining = withdrawal_amount.clone();
+pub fn set_swap_opening_date(
+    deps: DepsMut,
+    _env: Env,
+    swap_opening_date: Timestamp,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    config.swap_opening_date = swap_opening_date;
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::default())
+}
+
+/// Creates a pending request tracking the reply chain started on behalf of a user
+fn create_pending_request(
+    storage: &mut dyn Storage,
+    user_address: String,
+    funds: Vec<Coin>,
+    user_fury_amount: Uint128,
+    pair_id: Option<String>,
+    referrer: Option<Addr>,
+) -> StdResult<u64> {
+    let request_id = PENDING_REQUEST_ID.may_load(storage)?.unwrap_or_default() + 1;
+    PENDING_REQUEST_ID.save(storage, &request_id)?;
+    PENDING_REQUESTS.save(
+        storage,
+        U64Key::new(request_id),
+        &PendingRequest {
+            request_id,
+            user_address,
+            status: PendingRequestStatus::UserTransfer,
+            sub_req_id: 0,
+            funds,
+            user_fury_amount,
+            treasury_address: None,
+            treasury_fury_amount: Uint128::zero(),
+            bond_id: None,
+            ust_amount: Uint128::zero(),
+            quote_token_amount: Uint128::zero(),
+            pair_id,
+            referrer,
+            platform_fees: Uint128::zero(),
+            referral_fees: Uint128::zero(),
+        },
+    )?;
+    Ok(request_id)
+}
+
+/// Moves a pending request to the step dispatched as submessage `sub_req_id`
+fn advance_pending_request(
+    storage: &mut dyn Storage,
+    request_id: u64,
+    sub_req_id: u64,
+    status: PendingRequestStatus,
+) -> StdResult<PendingRequest> {
+    PENDING_REQUESTS.update(storage, U64Key::new(request_id), |request| {
+        let mut request = request.ok_or_else(|| StdError::not_found("pending request"))?;
+        request.sub_req_id = sub_req_id;
+        request.status = status;
+        Ok(request)
+    })
+}
+
+/// Credits the referrer of a completed request and returns the messages collecting its platform
+/// fees, the referrer's share is pulled to the proxy
+fn collect_pending_request_fees(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    request: &PendingRequest,
+) -> StdResult<Vec<CosmosMsg>> {
+    if let Some(referrer) = &request.referrer {
+        credit_referrer(
+            deps.storage,
+            referrer,
+            &config.quote_asset,
+            request.referral_fees,
+        )?;
+    }
+    let mut fee_msgs = collect_platform_fees(
+        deps.as_ref(),
+        config,
+        request.user_address.clone(),
+        request.platform_fees - request.referral_fees,
+    )?;
+    fee_msgs.extend(collect_referral_fees(
+        env,
+        &config.quote_asset,
+        request.user_address.clone(),
+        request.referral_fees,
+    )?);
+    Ok(fee_msgs)
+}
 
-    // parse bonding to check maturity and sort with descending order of timestamp
-    let mut bonds = Vec::new();
-    let mut updated_bonds = Vec::new();
-    let mut earliest = FAR_IN_FUTURE;
-    let mut earliest_amount = Uint128::zero();
+/// Reverses what a pending request has done so far: the recorded bond is removed and the Fury,
+/// native funds and platform fees held by the proxy for it are returned. The referrer is only
+/// credited once the request completes, so there is no referral credit to withdraw
+fn rollback_pending_request(
+    deps: DepsMut,
+    env: Env,
+    request: PendingRequest,
+) -> Result<Response, ContractError> {
+    let config = load_config(deps.storage, request.pair_id.as_deref())?;
+    let mut resp = Response::new();
 
-    let all_bonds = BONDED_REWARDS_DETAILS.may_load(deps.storage, receiver.clone())?;
-    match all_bonds {
-        Some(some_bonds) => {
-            bonds = some_bonds;
-            let mut updated_bond;
-            for bond in bonds {
-                println!(
-                    "receiver {:?} timestamp  {:?} duration  {:?} amount {:?}",
-                    receiver_addr,
-                    bond.bonding_start_timestamp,
-                    bond.bonding_period,
-                    bond.bonded_amount
-                );
-
-                updated_bond = bond.clone();
-                let _bond_timestamp;
-                if bond.bonding_start_timestamp.seconds() == Timestamp::from_seconds(0u64).seconds()
-                {
-                    _bond_timestamp = config.swap_opening_date;
-                } else {
-                    _bond_timestamp = bond.bonding_start_timestamp;
-                }
-                if _bond_timestamp.plus_seconds(bond.bonding_period).seconds() < earliest {
-                    earliest = _bond_timestamp.plus_seconds(bond.bonding_period).seconds();
-                    earliest_amount = bond.bonded_amount.clone();
-                }
-                if _bond_timestamp.plus_seconds(bond.bonding_period).seconds()
-                    < env.block.time.seconds()
-                {
-                    if amount_remaining > Uint128::zero() {
-                        if bond.bonded_amount > amount_remaining {
-                            unbonded_amount = amount_remaining;
-                            updated_bond.bonded_amount -= amount_remaining;
-                            amount_remaining = Uint128::zero();
-                            updated_bonds.push(updated_bond);
-                        } else {
-                            unbonded_amount += bond.bonded_amount;
-                            amount_remaining -= bond.bonded_amount;
-                        }
-                    } else {
-                        updated_bonds.push(updated_bond);
-                    }
-                } else {
-                    updated_bonds.push(updated_bond);
-                }
-            }
+    if let Some(bond_id) = request.bond_id {
+        // The bond may have been transferred since it was recorded
+        let owner = BOND_OWNERS
+            .may_load(deps.storage, U64Key::new(bond_id))?
+            .unwrap_or_else(|| request.user_address.clone());
+        if let Some(bond) = remove_bond(deps.storage, owner, bond_id)? {
+            record_bond_released(
+                deps.storage,
+                &bond,
+                bond.bonded_amount - bond.claimed_amount,
+                true,
+            )?;
+            BOND_STATS.update(
+                deps.storage,
+                bond.bond_type.to_string(),
+                |stats| -> StdResult<_> {
+                    let mut stats = stats.unwrap_or_default();
+                    stats.ust_raised = stats.ust_raised.saturating_sub(request.ust_amount);
+                    Ok(stats)
+                },
+            )?;
+            release_bond_capacity(
+                deps.storage,
+                &config,
+                bond.bond_type,
+                bond.bonded_amount,
+                env.block.time,
+            )?;
         }
-        None => {}
     }
 
-    if unbonded_amount == Uint128::zero() {
-        let message;
-        if earliest < FAR_IN_FUTURE {
-            message = format!(
-                "Earliest Withdrawal Amount {:?} at {:?}",
-                earliest_amount, earliest
-            );
-        } else {
-            message = format!("No Bonded Rewards");
+    let user_fury_received = request.status != PendingRequestStatus::UserTransfer;
+    let treasury_fury_received = request.status == PendingRequestStatus::Allowance
+        || request.status == PendingRequestStatus::ProvideLiquidity;
+
+    if request.status == PendingRequestStatus::ProvideLiquidity
+        && !request.user_fury_amount.is_zero()
+    {
+        resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.custom_token_address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::DecreaseAllowance {
+                spender: config.pool_pair_address.clone(),
+                amount: request.user_fury_amount,
+                expires: None,
+            })?,
+            funds: vec![],
+        }));
+    }
+    if let AssetInfo::Token { contract_addr } = &config.quote_asset {
+        if request.status == PendingRequestStatus::ProvideLiquidity
+            && !request.quote_token_amount.is_zero()
+        {
+            resp = resp
+                .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::DecreaseAllowance {
+                        spender: config.pool_pair_address.clone(),
+                        amount: request.quote_token_amount,
+                        expires: None,
+                    })?,
+                    funds: vec![],
+                }))
+                .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: request.user_address.clone(),
+                        amount: request.quote_token_amount,
+                    })?,
+                    funds: vec![],
+                }));
         }
-        return Err(ContractError::Std(StdError::generic_err(message)));
-    } else {
-        if amount_remaining > Uint128::zero() {
-            return Err(ContractError::Std(StdError::generic_err(format!(
-                "Withdraw Amount requested is more than Claimable {:?}",
-                unbonded_amount
-            ))));
+    }
+    if user_fury_received && !request.user_fury_amount.is_zero() {
+        resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.custom_token_address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: request.user_address.clone(),
+                amount: request.user_fury_amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+    if let Some(treasury_address) = request.treasury_address {
+        if treasury_fury_received && !request.treasury_fury_amount.is_zero() {
+            resp = resp.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.custom_token_address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: treasury_address.to_string(),
+                    amount: request.treasury_fury_amount,
+                })?,
+                funds: vec![],
+            }));
         }
     }
 
-    BONDED_REWARDS_DETAILS.save(deps.storage, receiver.clone(), &updated_bonds)?;
-
-    let mut rsp = Response::new();
-    //Send the platform fees to platform fee collector wallet
-    let mut funds_to_send = vec![];
-    for fund in info.funds {
-        let pf_asset = Asset {
-            info: AssetInfo::NativeToken {
-                denom: String::from("uusd"),
-            },
+    // Native platform fees are held by the proxy until the request completes, CW20 ones are only
+    // pulled then
+    let mut funds = request.funds;
+    if let AssetInfo::NativeToken { denom } = &config.quote_asset {
+        if !request.platform_fees.is_zero() {
+            match funds.iter_mut().find(|fund| &fund.denom == denom) {
+                Some(fund) => fund.amount += request.platform_fees,
+                None => funds.push(Coin {
+                    denom: denom.clone(),
+                    amount: request.platform_fees,
+                }),
+            }
+        }
+    }
+    let mut funds_to_refund = vec![];
+    for fund in funds {
+        if fund.amount.is_zero() {
+            continue;
+        }
+        let asset = Asset {
+            info: AssetInfo::NativeToken { denom: fund.denom },
             amount: fund.amount,
         };
-        let taxed_platform_fees = pf_asset.deduct_tax(&deps.querier)?;
-        funds_to_send.push(taxed_platform_fees);
+        funds_to_refund.push(asset.deduct_tax(&deps.querier)?);
+    }
+    if !funds_to_refund.is_empty() {
+        resp = resp.add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: request.user_address.clone(),
+            amount: funds_to_refund,
+        }));
     }
-    rsp = rsp.add_message(CosmosMsg::Bank(BankMsg::Send {
-        to_address: config.platform_fees_collector_wallet.into_string(),
-        amount: funds_to_send,
-    }));
-    let transfer_msg = Cw20ExecuteMsg::Transfer {
-        recipient: receiver,
-        amount: withdrawal_amount,
-    };
-    let exec = WasmMsg::Execute {
-        contract_addr: config.custom_token_address.to_string(),
-        msg: to_binary(&transfer_msg).unwrap(),
-        funds: vec![
-        // Coin {
-        //     denom: token_info.name.to_string(),
-        //     amount: price,
-        // },
-        ],
-    };
-    let send: SubMsg = SubMsg::new(exec);
-    let data_msg = format!("Amount {} transferred", withdrawal_amount).into_bytes();
 
-    rsp = rsp
-        .add_submessage(send)
-        .add_attribute("action", action)
-        .add_attribute("withdrawn", withdrawal_amount.clone().to_string())
-        .set_data(data_msg);
-    return Ok(rsp);
+    PENDING_REQUESTS.remove(deps.storage, U64Key::new(request.request_id));
+    SUB_MESSAGE_DETAILS.remove(deps.storage, request.sub_req_id.to_string());
+
+    Ok(resp
+        .add_attribute("action", "rollback_pending_request")
+        .add_attribute("request_id", request.request_id.to_string()))
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn swap(
+/// Lets the admin roll back a request that is stuck in the reply chain
+fn cancel_pending_request(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    offer_asset: Asset,
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    to: Option<Addr>,
+    request_id: u64,
 ) -> Result<Response, ContractError> {
-    let config: Config = CONFIG.load(deps.storage)?;
-    // Check if the swap_enable_date is passed
-    if config.swap_opening_date.nanos() > env.block.time.nanos() {
-        //return error
-        return Err(ContractError::Std(StdError::generic_err(format!(
-            "Swap is not enabled yet!!!",
-        ))));
-    }
-    // Swap is enabled so proceed
-    // Check if platform fees is provided
-    let required_ust_fees: Uint128;
-    required_ust_fees = query_platform_fees(
-        deps.as_ref(),
-        to_binary(&ExecuteMsg::Swap {
-            offer_asset: offer_asset.clone(),
-            belief_price: belief_price.clone(),
-            max_spread: max_spread.clone(),
-            to: Some(to.clone().unwrap().into_string()),
-        })?,
-    )?;
-    let mut fees = Uint128::zero();
-    for fund in info.funds.clone() {
-        if fund.denom == "uusd" {
-            fees = fees.checked_add(fund.amount).unwrap();
-        }
-    }
-    if offer_asset.is_native_token() {
-        fees = fees.checked_sub(offer_asset.amount).unwrap();
-    }
-    let native_tax = offer_asset.compute_tax(&deps.querier)?;
-    fees = fees.checked_sub(native_tax).unwrap();
-    if fees < required_ust_fees {
-        return Err(ContractError::InsufficientFees {
-            required: required_ust_fees,
-            received: fees,
-        });
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin_address {
+        return Err(ContractError::Unauthorized {});
     }
-    //Platform fees provided is good
-    //If offer asset is custom token (sell fury),
-    // transfer the asset amount of fury tokens to this contract
-    let to_address: Option<String>;
-    match to {
-        Some(to_addr) => to_address = Some(to_addr.into_string()),
-        None => to_address = Some(info.sender.clone().into_string()),
+    let request = PENDING_REQUESTS
+        .may_load(deps.storage, U64Key::new(request_id))?
+        .ok_or(ContractError::PendingRequestNotFound { request_id })?;
+    rollback_pending_request(deps, env, request)
+}
+
+fn bond_capacity_config(config: &Config, bond_type: BondType) -> &BondCapacityConfig {
+    match bond_type {
+        BondType::Pair => &config.pair_bond_capacity,
+        BondType::Native => &config.native_bond_capacity,
     }
-    //Remove platform fees from funds and transfer it to platform_fees_collector_wallet
-    let mut funds_to_send = vec![];
-    let platform_fees = Coin::new(required_ust_fees.u128(), String::from("uusd"));
-    if offer_asset.is_native_token() {
-        if let AssetInfo::NativeToken { denom, .. } = &offer_asset.info {
-            funds_to_send = vec![Coin {
-                denom: denom.to_string(),
-                amount: offer_asset.amount,
-            }];
+}
+
+/// Loads the Fury issued for a bond type, with the epoch usage reset if a new epoch has started
+fn load_bond_capacity_usage(
+    storage: &dyn Storage,
+    config: &Config,
+    bond_type: BondType,
+    now: Timestamp,
+) -> StdResult<BondCapacityUsage> {
+    let mut usage = BOND_CAPACITY_USAGE
+        .may_load(storage, bond_type.to_string())?
+        .unwrap_or_default();
+    let epoch_length = bond_capacity_config(config, bond_type).epoch_length_in_sec;
+    if epoch_length > 0 {
+        let epoch_start = now.seconds() - now.seconds() % epoch_length;
+        if usage.epoch_start != epoch_start {
+            usage.epoch_start = epoch_start;
+            usage.bonded_in_epoch = Uint128::zero();
         }
     }
-    if !offer_asset.is_native_token() {
-        return forward_swap_to_astro(
-            deps,
-            env,
-            info,
-            Cw20HookMsg::Swap {
-                belief_price: belief_price,
-                max_spread: max_spread,
-                to: to_address,
-            },
-            offer_asset.amount,
-            funds_to_send,
-            platform_fees,
-        );
+    Ok(usage)
+}
+
+/// Records Fury issued for a new bond, failing if it exceeds the epoch or programme capacity
+fn consume_bond_capacity(
+    storage: &mut dyn Storage,
+    config: &Config,
+    bond_type: BondType,
+    amount: Uint128,
+    now: Timestamp,
+) -> Result<(), ContractError> {
+    let bond_capacity = bond_capacity_config(config, bond_type);
+    let mut usage = load_bond_capacity_usage(storage, config, bond_type, now)?;
+    if let Some(max_fury_per_epoch) = bond_capacity.max_fury_per_epoch {
+        let remaining = max_fury_per_epoch.saturating_sub(usage.bonded_in_epoch);
+        if amount > remaining {
+            return Err(ContractError::EpochBondCapacityExceeded {
+                required: amount,
+                remaining,
+            });
+        }
     }
-    //Check if assets provided are native tokens
-    offer_asset.info.check(deps.api)?;
-    let swap_msg = PairExecuteMsg::Swap {
-        offer_asset: offer_asset,
-        belief_price: belief_price,
-        max_spread: max_spread,
-        to: to_address,
-    };
-    let exec = WasmMsg::Execute {
-        contract_addr: config.pool_pair_address.to_string(),
-        msg: to_binary(&swap_msg).unwrap(),
-        funds: funds_to_send,
-    };
-    let mut send: SubMsg = SubMsg::new(exec);
-    let mut sub_req_id = 1;
-    if let Some(mut req_id) = SUB_REQ_ID.may_load(deps.storage)? {
-        req_id += 1;
-        SUB_REQ_ID.save(deps.storage, &req_id)?;
-        sub_req_id = req_id;
-    } else {
-        SUB_REQ_ID.save(deps.storage, &sub_req_id)?;
+    if let Some(total_budget) = bond_capacity.total_budget {
+        let remaining = total_budget.saturating_sub(usage.total_bonded);
+        if amount > remaining {
+            return Err(ContractError::BondBudgetExceeded {
+                required: amount,
+                remaining,
+            });
+        }
     }
-    send.id = sub_req_id;
-    send.reply_on = ReplyOn::Always;
+    usage.bonded_in_epoch += amount;
+    usage.total_bonded += amount;
+    BOND_CAPACITY_USAGE.save(storage, bond_type.to_string(), &usage)?;
+    Ok(())
+}
 
-    let mut resp = Response::new();
-    resp = resp.add_submessage(send);
-    let data_msg = format!("Swapping {:?}", swap_msg).into_bytes();
+/// Gives back the capacity of a bond that has been rolled back
+fn release_bond_capacity(
+    storage: &mut dyn Storage,
+    config: &Config,
+    bond_type: BondType,
+    amount: Uint128,
+    now: Timestamp,
+) -> StdResult<()> {
+    let mut usage = load_bond_capacity_usage(storage, config, bond_type, now)?;
+    usage.bonded_in_epoch = usage.bonded_in_epoch.saturating_sub(amount);
+    usage.total_bonded = usage.total_bonded.saturating_sub(amount);
+    BOND_CAPACITY_USAGE.save(storage, bond_type.to_string(), &usage)
+}
 
-    //Add bank message to transfer platform fees to platform fee collector wallet
-    let pf_asset = Asset{info:AssetInfo::NativeToken{denom: String::from("uusd")}, amount: platform_fees.amount};
-    let taxed_platform_fees = pf_asset.deduct_tax(&deps.querier)?;
+/// Fury the reward wallet can transfer to the proxy, limited by its balance and allowance
+fn query_reward_wallet_available(
+    deps: Deps,
+    config: &Config,
+    reward_wallet: String,
+    proxy_address: String,
+) -> StdResult<Uint128> {
+    let balance: BalanceResponse = deps.querier.query_wasm_smart(
+        config.custom_token_address.clone(),
+        &Cw20QueryMsg::Balance {
+            address: reward_wallet.clone(),
+        },
+    )?;
+    let allowance: AllowanceResponse = deps.querier.query_wasm_smart(
+        config.custom_token_address.clone(),
+        &Cw20QueryMsg::Allowance {
+            owner: reward_wallet,
+            spender: proxy_address,
+        },
+    )?;
+    Ok(balance.balance.min(allowance.allowance))
+}
 
-    resp = resp.add_message(CosmosMsg::Bank(BankMsg::Send {
-        to_address: config.platform_fees_collector_wallet.into_string(),
-        amount: vec![taxed_platform_fees],
-    }));
+/// Adds a new bond to the statistics of its bond type and to the maturity histogram
+fn record_bond_issued(
+    storage: &mut dyn Storage,
+    bond: &BondedRewardsDetails,
+    ust_amount: Uint128,
+) -> StdResult<()> {
+    BOND_STATS.update(
+        storage,
+        bond.bond_type.to_string(),
+        |stats| -> StdResult<_> {
+            let mut stats = stats.unwrap_or_default();
+            stats.outstanding_bonds += 1;
+            stats.outstanding_fury += bond.bonded_amount;
+            stats.ust_raised += ust_amount;
+            Ok(stats)
+        },
+    )?;
+    update_maturity_bucket(storage, bond, |amount| *amount += bond.bonded_amount)
+}
 
-    Ok(resp
-        .add_attribute("action", "Sending swap message")
-        .set_data(data_msg))
+/// Removes Fury paid out or forfeited from a bond from the statistics and the maturity histogram
+fn record_bond_released(
+    storage: &mut dyn Storage,
+    bond: &BondedRewardsDetails,
+    amount: Uint128,
+    bond_closed: bool,
+) -> StdResult<()> {
+    BOND_STATS.update(
+        storage,
+        bond.bond_type.to_string(),
+        |stats| -> StdResult<_> {
+            let mut stats = stats.unwrap_or_default();
+            if bond_closed {
+                stats.outstanding_bonds = stats.outstanding_bonds.saturating_sub(1);
+            }
+            stats.outstanding_fury = stats.outstanding_fury.saturating_sub(amount);
+            Ok(stats)
+        },
+    )?;
+    update_maturity_bucket(storage, bond, |bucket_amount| {
+        *bucket_amount = bucket_amount.saturating_sub(amount)
+    })
 }
 
-pub fn set_swap_opening_date(
-    deps: DepsMut,
-    _env: Env,
-    swap_opening_date: Timestamp,
-) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    config.swap_opening_date = swap_opening_date;
-    CONFIG.save(deps.storage, &config)?;
-    Ok(Response::default())
+/// Applies a change to the bucket of the maturity histogram the bond belongs to
+fn update_maturity_bucket<F: FnOnce(&mut Uint128)>(
+    storage: &mut dyn Storage,
+    bond: &BondedRewardsDetails,
+    change: F,
+) -> StdResult<()> {
+    // Bonds issued before the swap opening mature relative to the swap opening date
+    let (histogram, key) = if bond.bonding_start_timestamp.seconds() == 0 {
+        (&PRE_OPENING_MATURITIES, bond.bonding_period)
+    } else {
+        let maturity = bond.bonding_start_timestamp.seconds() + bond.bonding_period;
+        (&MATURITY_HISTOGRAM, maturity / SECONDS_PER_DAY)
+    };
+    let mut bucket = histogram
+        .may_load(storage, U64Key::new(key))?
+        .unwrap_or_default();
+    match bond.bond_type {
+        BondType::Pair => change(&mut bucket.pair),
+        BondType::Native => change(&mut bucket.native),
+    }
+    if bucket.pair.is_zero() && bucket.native.is_zero() {
+        histogram.remove(storage, U64Key::new(key));
+    } else {
+        histogram.save(storage, U64Key::new(key), &bucket)?;
+    }
+    Ok(())
+}
+
+/// Removes a bond from the bonds of a user, returning it if it was found
+fn remove_bond(
+    storage: &mut dyn Storage,
+    user_address: String,
+    bond_id: u64,
+) -> StdResult<Option<BondedRewardsDetails>> {
+    let key = (user_address.as_str(), U64Key::new(bond_id));
+    let removed_bond = BONDED_REWARDS_DETAILS.may_load(storage, key.clone())?;
+    if removed_bond.is_some() {
+        BONDED_REWARDS_DETAILS.remove(storage, key);
+        BOND_OWNERS.remove(storage, U64Key::new(bond_id));
+        BOND_APPROVALS.remove(storage, U64Key::new(bond_id));
+    }
+    Ok(removed_bond)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(mut deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     let result = msg.result;
     match result {
         ContractResult::Ok(sub_msg) => {
+            let mut resp = Response::new();
             let sub_msg_id = msg.id;
             let sub_message_details =
                 SUB_MESSAGE_DETAILS.may_load(deps.storage, sub_msg_id.to_string())?;
@@ -1260,6 +4834,43 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                             // Remove the saved submessage from storage
                             SUB_MESSAGE_DETAILS.remove(deps.storage, msg.id.to_string());
                         }
+                        SubMessageType::RouteSubMsg => {
+                            // Remove the saved submessage from storage
+                            SUB_MESSAGE_DETAILS.remove(deps.storage, msg.id.to_string());
+                            return swap_route_proceeds_to_fury(
+                                deps,
+                                env,
+                                from_binary(&smd.sub_message_payload)?,
+                                smd.user_address,
+                            );
+                        }
+                        SubMessageType::SwapSubMsg => {
+                            // Remove the saved submessage from storage
+                            SUB_MESSAGE_DETAILS.remove(deps.storage, msg.id.to_string());
+                            return forward_swap_proceeds(
+                                deps,
+                                from_binary(&smd.sub_message_payload)?,
+                                &sub_msg.events,
+                            );
+                        }
+                        SubMessageType::WithdrawLiquiditySubMsg => {
+                            // Remove the saved submessage from storage
+                            SUB_MESSAGE_DETAILS.remove(deps.storage, msg.id.to_string());
+                            return forward_withdrawn_assets(
+                                deps,
+                                from_binary(&smd.sub_message_payload)?,
+                                &sub_msg.events,
+                            );
+                        }
+                        SubMessageType::GeneratorSubMsg => {
+                            // Remove the saved submessage from storage
+                            SUB_MESSAGE_DETAILS.remove(deps.storage, msg.id.to_string());
+                            return forward_proxy_rewards(
+                                deps.as_ref(),
+                                env,
+                                from_binary(&smd.sub_message_payload)?,
+                            );
+                        }
                         SubMessageType::ProvideLiquiditySubMsg => {
                             // Remove the saved submessage from storage
                             SUB_MESSAGE_DETAILS.remove(deps.storage, msg.id.to_string());
@@ -1283,13 +4894,19 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                                         smd.funds,
                                         smd.user_address,
                                         smd.is_fury_provided,
-                                        None,
+                                        Uint128::zero(),
+                                        smd.request_id,
+                                        smd.term_id,
                                     );
                                 } else if smd.next_action == SubMessageNextAction::TransferToNativeInvestmentReceiveWallet{
                                     return transfer_native_assets_to_native_investment_receive_wallet(
                                         deps,
+                                        env,
+                                        assets,
                                         receiver,
                                         smd.funds,
+                                        smd.user_address,
+                                        smd.request_id,
                                     );
                                 } else if smd.next_action == SubMessageNextAction::IncreaseAllowance
                                 {
@@ -1303,6 +4920,7 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                                         smd.funds,
                                         smd.user_address,
                                         smd.is_fury_provided,
+                                        smd.request_id,
                                     );
                                 } else if smd.next_action == SubMessageNextAction::ProvideLiquidity
                                 {
@@ -1314,7 +4932,27 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                                         auto_stake,
                                         receiver,
                                         smd.funds,
+                                        smd.request_id,
                                     );
+                                } else if smd.next_action == SubMessageNextAction::CompletePendingRequest
+                                {
+                                    let config = load_request_config(deps.storage, smd.request_id)?;
+                                    let request = PENDING_REQUESTS.load(deps.storage, U64Key::new(smd.request_id))?;
+                                    PENDING_REQUESTS.remove(deps.storage, U64Key::new(smd.request_id));
+                                    resp = resp.add_messages(collect_pending_request_fees(
+                                        deps.branch(),
+                                        &env,
+                                        &config,
+                                        &request,
+                                    )?);
+                                    if receiver == Some(env.contract.address.to_string()) {
+                                        resp = resp.add_submessages(stake_protocol_liquidity(
+                                            deps,
+                                            &env,
+                                            &config,
+                                            &sub_msg.events,
+                                        )?);
+                                    }
                                 }
                                 }
                                 _ => {
@@ -1329,7 +4967,6 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                 None => {}
             }
             // For all fall-through messages respond with success
-            let mut resp = Response::new();
             for event in sub_msg.events {
                 resp = resp.add_attributes(event.attributes);
             }
@@ -1339,59 +4976,189 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
             }
             return Ok(resp);
         }
-        ContractResult::Err(error) => {
-            return Err(ContractError::Std(StdError::generic_err(format!(
-                "Received error: {:?}",
-                error
-            ))));
+        ContractResult::Err(error) => {
+            // Roll back the request this submessage was a step of, if any
+            if let Some(smd) = SUB_MESSAGE_DETAILS.may_load(deps.storage, msg.id.to_string())? {
+                if let Some(request) =
+                    PENDING_REQUESTS.may_load(deps.storage, U64Key::new(smd.request_id))?
+                {
+                    let resp = rollback_pending_request(deps, env, request)?;
+                    return Ok(resp.add_attribute("error", error));
+                }
+            }
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Received error: {:?}",
+                error
+            ))));
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Configuration { pair_id } => to_binary(&query_configuration(deps, pair_id)?),
+        QueryMsg::Pair { pair_id } => to_binary(&query_pair(deps, pair_id)?),
+        QueryMsg::Pool { pair_id } => to_binary(&query_pool(deps, pair_id)?),
+        QueryMsg::Simulation {
+            offer_asset,
+            pair_id,
+        } => to_binary(&query_simulation(deps, offer_asset, pair_id)?),
+        QueryMsg::ReverseSimulation { ask_asset, pair_id } => {
+            to_binary(&query_reverse_simulation(deps, ask_asset, pair_id)?)
+        }
+        QueryMsg::CumulativePrices { pair_id } => {
+            to_binary(&query_cumulative_prices(deps, pair_id)?)
+        }
+        QueryMsg::Pairs {} => to_binary(&query_pairs(deps)?),
+        QueryMsg::Status {} => to_binary(&load_pause_status(deps.storage)?),
+        QueryMsg::SimulateProvidePairForReward {
+            user_address,
+            assets,
+            term_id,
+            pair_id,
+        } => to_binary(&query_simulate_provide_pair_for_reward(
+            deps,
+            env,
+            user_address,
+            assets,
+            term_id,
+            pair_id,
+        )?),
+        QueryMsg::SimulateProvideNativeForReward {
+            user_address,
+            asset,
+            term_id,
+            pair_id,
+        } => to_binary(&query_simulate_provide_native_for_reward(
+            deps,
+            env,
+            user_address,
+            asset,
+            term_id,
+            pair_id,
+        )?),
+        QueryMsg::SimulateSwap {
+            offer_asset,
+            pay_fees_in_fury,
+            deduct_fees_from_proceeds,
+            pair_id,
+        } => to_binary(&query_simulate_swap(
+            deps,
+            env,
+            offer_asset,
+            pay_fees_in_fury.unwrap_or(false),
+            deduct_fees_from_proceeds.unwrap_or(false),
+            pair_id,
+        )?),
+        QueryMsg::EarlyAccess {
+            address,
+            cap,
+            proof,
+        } => to_binary(&query_early_access(deps, address, cap, proof)?),
+        QueryMsg::ReferralRewards { referrer } => {
+            to_binary(&query_referral_rewards(deps, referrer)?)
+        }
+        QueryMsg::Referrers { start_after, limit } => {
+            to_binary(&query_referrers(deps, start_after, limit)?)
         }
-    }
-}
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Configuration {} => to_binary(&query_configuration(deps)?),
-        QueryMsg::Pair {} => to_binary(&query_pair(deps)?),
-        QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
-        QueryMsg::Simulation { offer_asset } => to_binary(&query_simulation(deps, offer_asset)?),
-        QueryMsg::ReverseSimulation { ask_asset } => {
-            to_binary(&query_reverse_simulation(deps, ask_asset)?)
-        }
-        QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps)?),
+        QueryMsg::ProtocolLiquidity {} => to_binary(&query_protocol_liquidity(deps)?),
         QueryMsg::GetSwapOpeningDate {} => to_binary(&query_swap_opening_date(deps)?),
         QueryMsg::GetBondingDetails { user_address } => {
             to_binary(&query_bonding_details(deps, user_address)?)
         }
-        QueryMsg::GetUstEquivalentToFury { fury_count } => {
-            to_binary(&get_ust_equivalent_to_fury(deps, fury_count)?)
+        QueryMsg::GetClaimableAmount { user_address } => {
+            to_binary(&query_claimable_amount(deps, env, user_address)?)
+        }
+        QueryMsg::GetUstEquivalentToFury {
+            fury_count,
+            pair_id,
+        } => {
+            let config = load_config(deps.storage, pair_id.as_deref())?;
+            to_binary(&get_ust_equivalent_to_fury(deps, &config, fury_count)?)
+        }
+        QueryMsg::GetFuryEquivalentToUst { ust_count, pair_id } => {
+            let config = load_config(deps.storage, pair_id.as_deref())?;
+            to_binary(&get_fury_equivalent_to_ust(deps, &config, ust_count)?)
+        }
+        QueryMsg::BondTerms {} => to_binary(&query_bond_terms(deps)?),
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => to_binary(&query_owner_of(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or(false),
+        )?),
+        QueryMsg::ContractInfo {} => to_binary(&ContractInfoResponse {
+            name: BOND_TOKEN_NAME.to_string(),
+            symbol: BOND_TOKEN_SYMBOL.to_string(),
+        }),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&AllNftInfoResponse {
+            access: query_owner_of(
+                deps,
+                env,
+                token_id.clone(),
+                include_expired.unwrap_or(false),
+            )?,
+            info: query_nft_info(deps, token_id)?,
+        }),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_tokens(deps, owner, start_after, limit)?),
+        QueryMsg::NumTokens {} => to_binary(&query_num_tokens(deps)?),
+        QueryMsg::AllBonds { start_after, limit } => {
+            to_binary(&read_all_bonds(deps.storage, start_after, limit)?)
+        }
+        QueryMsg::BondStatistics {} => to_binary(&query_bond_statistics(deps, env)?),
+        QueryMsg::MaturityHistogram { start_after, limit } => {
+            to_binary(&query_maturity_histogram(deps, start_after, limit)?)
         }
-        QueryMsg::GetFuryEquivalentToUst { ust_count } => {
-            to_binary(&get_fury_equivalent_to_ust(deps, ust_count)?)
+        QueryMsg::BondCapacity {} => to_binary(&query_bond_capacity(deps, env)?),
+        QueryMsg::PendingRequests { start_after, limit } => {
+            to_binary(&read_pending_requests(deps.storage, start_after, limit)?)
+        }
+        QueryMsg::QueryPlatformFees { msg, user_address } => {
+            to_binary(&query_platform_fees(deps, env, msg, user_address)?)
+        }
+        QueryMsg::QueryPlatformFeesInFury { msg, user_address } => {
+            let config = load_message_config(deps.storage, &msg)?;
+            let platform_fees = query_platform_fees(deps, env, msg, user_address)?;
+            to_binary(&fury_platform_fees(deps, &config, platform_fees)?)
         }
-        QueryMsg::QueryPlatformFees { msg } => to_binary(&query_platform_fees(deps, msg)?),
     }
 }
 
-fn query_configuration(deps: Deps) -> StdResult<Config> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn query_configuration(deps: Deps, pair_id: Option<String>) -> StdResult<Config> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
     Ok(config)
 }
 
-fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn query_pool(deps: Deps, pair_id: Option<String>) -> StdResult<PoolResponse> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
     deps.querier
         .query_wasm_smart(config.pool_pair_address, &Pool {})
 }
 
-fn query_pair(deps: Deps) -> StdResult<PairInfo> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn query_pair(deps: Deps, pair_id: Option<String>) -> StdResult<PairInfo> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
     deps.querier
         .query_wasm_smart(config.pool_pair_address, &Pair {})
 }
 
-fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationResponse> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn query_simulation(
+    deps: Deps,
+    offer_asset: Asset,
+    pair_id: Option<String>,
+) -> StdResult<SimulationResponse> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
     deps.querier.query_wasm_smart(
         config.pool_pair_address,
         &Simulation {
@@ -1400,8 +5167,12 @@ fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationRespo
     )
 }
 
-fn query_reverse_simulation(deps: Deps, ask_asset: Asset) -> StdResult<ReverseSimulationResponse> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn query_reverse_simulation(
+    deps: Deps,
+    ask_asset: Asset,
+    pair_id: Option<String>,
+) -> StdResult<ReverseSimulationResponse> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
     deps.querier.query_wasm_smart(
         config.pool_pair_address,
         &ReverseSimulation {
@@ -1410,12 +5181,272 @@ fn query_reverse_simulation(deps: Deps, ask_asset: Asset) -> StdResult<ReverseSi
     )
 }
 
-fn query_cumulative_prices(deps: Deps) -> StdResult<CumulativePricesResponse> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn query_cumulative_prices(
+    deps: Deps,
+    pair_id: Option<String>,
+) -> StdResult<CumulativePricesResponse> {
+    let config: Config = load_config(deps.storage, pair_id.as_deref())?;
     deps.querier
         .query_wasm_smart(config.pool_pair_address, &CumulativePrices {})
 }
 
+fn query_pairs(deps: Deps) -> StdResult<Vec<PairResponse>> {
+    PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (pair_id, settings) = item?;
+            Ok(PairResponse {
+                pair_id: String::from_utf8(pair_id)?,
+                settings,
+            })
+        })
+        .collect()
+}
+
+fn query_simulate_provide_pair_for_reward(
+    deps: Deps,
+    env: Env,
+    user_address: String,
+    assets: [Asset; 2],
+    term_id: Option<u64>,
+    pair_id: Option<String>,
+) -> StdResult<SimulateBondResponse> {
+    let config = load_config(deps.storage, pair_id.as_deref())?;
+    let platform_fees = query_platform_fees(
+        deps,
+        env.clone(),
+        to_binary(&ExecuteMsg::ProvidePairForReward {
+            assets: assets.clone(),
+            slippage_tolerance: None,
+            auto_stake: None,
+            term_id,
+            pair_id,
+            referrer: None,
+        })?,
+        Some(user_address.clone()),
+    )?;
+    let fury_amount_provided = assets
+        .iter()
+        .filter(|asset| is_fury(&config, &asset.info))
+        .map(|asset| asset.amount)
+        .sum();
+    simulate_bond(
+        deps,
+        &env,
+        &config,
+        user_address,
+        platform_fees,
+        quote_amount(&config, &assets),
+        fury_amount_provided,
+        true,
+        term_id,
+    )
+}
+
+fn query_simulate_provide_native_for_reward(
+    deps: Deps,
+    env: Env,
+    user_address: String,
+    asset: Asset,
+    term_id: Option<u64>,
+    pair_id: Option<String>,
+) -> StdResult<SimulateBondResponse> {
+    let config = load_config(deps.storage, pair_id.as_deref())?;
+    let platform_fees = query_platform_fees(
+        deps,
+        env.clone(),
+        to_binary(&ExecuteMsg::ProvideNativeForReward {
+            asset: asset.clone(),
+            slippage_tolerance: None,
+            auto_stake: None,
+            term_id,
+            pair_id,
+            referrer: None,
+        })?,
+        Some(user_address.clone()),
+    )?;
+    simulate_bond(
+        deps,
+        &env,
+        &config,
+        user_address,
+        platform_fees,
+        asset.amount,
+        Uint128::zero(),
+        false,
+        term_id,
+    )
+}
+
+/// Previews a bond with the pricing of the execute path, `is_fury_provided` picks a pair bond
+/// over a native one as it does there. The effective discount compares what the user pays,
+/// fees included, with the pool value of the bonded Fury
+#[allow(clippy::too_many_arguments)]
+fn simulate_bond(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    user_address: String,
+    platform_fees: Uint128,
+    ust_amount_provided: Uint128,
+    fury_amount_provided: Uint128,
+    is_fury_provided: bool,
+    term_id: Option<u64>,
+) -> StdResult<SimulateBondResponse> {
+    let quote = quote_bond(
+        deps,
+        config,
+        ust_amount_provided,
+        fury_amount_provided,
+        is_fury_provided,
+        term_id,
+    )
+    .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let bonding_start = if config.swap_opening_date < env.block.time {
+        env.block.time
+    } else {
+        config.swap_opening_date
+    };
+    let paid = ust_amount_provided
+        + get_ust_equivalent_to_fury(deps, config, fury_amount_provided)?
+        + platform_fees;
+    let bonded_value = get_ust_equivalent_to_fury(deps, config, quote.fury_amount)?;
+    let effective_discount = if bonded_value > paid {
+        Decimal::from_ratio(bonded_value - paid, bonded_value)
+    } else {
+        Decimal::zero()
+    };
+    let bonds_for_user = read_user_bonds(deps.storage, &user_address)?.len() as u64;
+    Ok(SimulateBondResponse {
+        platform_fees,
+        bond_type: quote.bond_type,
+        fury_amount: quote.fury_amount,
+        discount_rate: quote.discount_rate,
+        effective_discount,
+        bonding_period_in_sec: quote.bonding_period,
+        maturity: bonding_start.plus_seconds(quote.bonding_period),
+        vesting_schedule: quote.vesting_schedule,
+        bond_limit_reached: config.max_bonding_limit_per_user <= bonds_for_user,
+    })
+}
+
+fn query_simulate_swap(
+    deps: Deps,
+    env: Env,
+    offer_asset: Asset,
+    pay_fees_in_fury: bool,
+    deduct_fees_from_proceeds: bool,
+    pair_id: Option<String>,
+) -> StdResult<SimulateSwapResponse> {
+    let config = load_config(deps.storage, pair_id.as_deref())?;
+    let required_fees = query_platform_fees(
+        deps,
+        env,
+        to_binary(&ExecuteMsg::Swap {
+            offer_asset: offer_asset.clone(),
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            pay_fees_in_fury: Some(pay_fees_in_fury),
+            deduct_fees_from_proceeds: Some(deduct_fees_from_proceeds),
+            pair_id,
+            referrer: None,
+        })?,
+        None,
+    )?;
+    // Fees deducted from the proceeds are taken in the returned asset, as on reply
+    let (platform_fees, fury_fees, proceeds_fees) = if deduct_fees_from_proceeds {
+        let proceeds_fees = if is_fury(&config, &offer_asset.info) {
+            required_fees
+        } else {
+            fury_platform_fees(deps, &config, required_fees)?
+        };
+        (Uint128::zero(), Uint128::zero(), proceeds_fees)
+    } else {
+        let (platform_fees, fury_fees) =
+            platform_fees_by_asset(deps, &config, required_fees, pay_fees_in_fury)?;
+        (platform_fees, fury_fees, Uint128::zero())
+    };
+    let simulation: SimulationResponse = deps
+        .querier
+        .query_wasm_smart(config.pool_pair_address, &Simulation { offer_asset })?;
+    Ok(SimulateSwapResponse {
+        platform_fees,
+        fury_fees,
+        return_amount: simulation.return_amount,
+        spread_amount: simulation.spread_amount,
+        commission_amount: simulation.commission_amount,
+        net_return_amount: simulation.return_amount.saturating_sub(proceeds_fees),
+    })
+}
+
+fn query_early_access(
+    deps: Deps,
+    address: String,
+    cap: Option<Uint128>,
+    proof: Option<Vec<String>>,
+) -> StdResult<EarlyAccessResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let address = addr_validate_to_lower(deps.api, &address)?;
+    let mut allowance = EARLY_ACCESS_ALLOWANCES
+        .may_load(deps.storage, address.as_str())?
+        .unwrap_or_default();
+    let mut eligible = EARLY_ACCESS_ALLOWANCES.has(deps.storage, address.as_str());
+    if let (Some(cap), Some(proof)) = (cap, proof) {
+        if verify_early_access_proof(&config, &address, cap, &proof).is_ok() {
+            allowance.cap = allowance.cap.max(cap);
+            eligible = true;
+        }
+    }
+    Ok(EarlyAccessResponse {
+        eligible,
+        window_start: early_access_window_start(&config),
+        cap: allowance.cap,
+        used: allowance.used,
+        remaining: allowance.cap.saturating_sub(allowance.used),
+    })
+}
+
+fn query_referral_rewards(deps: Deps, referrer: String) -> StdResult<ReferralRewardsResponse> {
+    let referrer = addr_validate_to_lower(deps.api, &referrer)?;
+    let rewards = REFERRAL_REWARDS
+        .may_load(deps.storage, referrer.as_str())?
+        .unwrap_or_default();
+    Ok(ReferralRewardsResponse {
+        referrer: referrer.to_string(),
+        claimable: rewards.claimable,
+        total_earned: rewards.total_earned,
+    })
+}
+
+fn query_referrers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ReferralRewardsResponse>> {
+    Ok(read_referral_rewards(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(referrer, rewards)| ReferralRewardsResponse {
+            referrer,
+            claimable: rewards.claimable,
+            total_earned: rewards.total_earned,
+        })
+        .collect())
+}
+
+fn query_protocol_liquidity(deps: Deps) -> StdResult<Vec<ProtocolLiquidityResponse>> {
+    PROTOCOL_LIQUIDITY
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (lp_token, amount) = item?;
+            Ok(ProtocolLiquidityResponse {
+                lp_token: String::from_utf8(lp_token)?,
+                amount,
+            })
+        })
+        .collect()
+}
+
 fn query_swap_opening_date(deps: Deps) -> StdResult<Timestamp> {
     let config: Config = CONFIG.load(deps.storage)?;
     Ok(config.swap_opening_date)
@@ -1425,23 +5456,215 @@ fn query_bonding_details(
     deps: Deps,
     user_address: String,
 ) -> StdResult<Option<Vec<BondedRewardsDetails>>> {
-    let bonding_details = BONDED_REWARDS_DETAILS.may_load(deps.storage, user_address.clone())?;
-    Ok(bonding_details)
+    let bonding_details = read_user_bonds(deps.storage, &user_address)?;
+    if bonding_details.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(bonding_details))
+}
+
+fn query_claimable_amount(deps: Deps, env: Env, user_address: String) -> StdResult<Uint128> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let bonds = read_user_bonds(deps.storage, &user_address)?;
+    let mut claimable = Uint128::zero();
+    for bond in bonds {
+        claimable += claimable_amount(&config, &bond, env.block.time);
+    }
+    Ok(claimable)
+}
+
+fn query_bond_terms(deps: Deps) -> StdResult<Vec<BondTerm>> {
+    BOND_TERMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, term) = item?;
+            Ok(term)
+        })
+        .collect()
+}
+
+fn query_owner_of(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<OwnerOfResponse> {
+    let bond_id = parse_token_id(&token_id).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let owner = BOND_OWNERS.load(deps.storage, U64Key::new(bond_id))?;
+    let approvals = BOND_APPROVALS
+        .may_load(deps.storage, U64Key::new(bond_id))?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|approval| include_expired || !approval.expires.is_expired(&env.block))
+        .collect();
+    Ok(OwnerOfResponse { owner, approvals })
+}
+
+fn query_nft_info(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<NftInfoResponse<BondedRewardsDetails>> {
+    let bond_id = parse_token_id(&token_id).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let owner = BOND_OWNERS.load(deps.storage, U64Key::new(bond_id))?;
+    let bond = BONDED_REWARDS_DETAILS.load(deps.storage, (&owner, U64Key::new(bond_id)))?;
+    Ok(NftInfoResponse {
+        token_uri: None,
+        extension: bond,
+    })
+}
+
+fn query_tokens(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let owner = addr_validate_to_lower(deps.api, &owner)?;
+    let start_after = match start_after {
+        Some(token_id) => {
+            Some(parse_token_id(&token_id).map_err(|e| StdError::generic_err(e.to_string()))?)
+        }
+        None => None,
+    };
+    let tokens = read_owner_bond_ids(deps.storage, owner.to_string(), start_after, limit)?
+        .iter()
+        .map(|bond_id| bond_id.to_string())
+        .collect();
+    Ok(TokensResponse { tokens })
+}
+
+/// Counts the outstanding bonds from the statistics kept per bond type
+fn query_num_tokens(deps: Deps) -> StdResult<NumTokensResponse> {
+    let count = BOND_STATS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, stats)| stats.outstanding_bonds))
+        .sum::<StdResult<u64>>()?;
+    Ok(NumTokensResponse { count })
+}
+
+fn query_bond_statistics(deps: Deps, env: Env) -> StdResult<BondStatisticsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    // Everything maturing up to and including today counts as matured
+    let today = env.block.time.seconds() / SECONDS_PER_DAY;
+    let mut matured = MaturityBucket::default();
+    for (day, bucket) in read_pre_opening_maturities(deps.storage, config.swap_opening_date)? {
+        if day <= today {
+            matured.add(&bucket);
+        }
+    }
+    for item in MATURITY_HISTOGRAM.range(
+        deps.storage,
+        None,
+        Some(Bound::inclusive(U64Key::new(today))),
+        Order::Ascending,
+    ) {
+        let (_, bucket) = item?;
+        matured.add(&bucket);
+    }
+    Ok(BondStatisticsResponse {
+        pair: query_bond_statistics_info(deps, BondType::Pair, matured.pair)?,
+        native: query_bond_statistics_info(deps, BondType::Native, matured.native)?,
+    })
+}
+
+fn query_bond_statistics_info(
+    deps: Deps,
+    bond_type: BondType,
+    matured_fury: Uint128,
+) -> StdResult<BondStatisticsInfo> {
+    let stats = BOND_STATS
+        .may_load(deps.storage, bond_type.to_string())?
+        .unwrap_or_default();
+    Ok(BondStatisticsInfo {
+        outstanding_bonds: stats.outstanding_bonds,
+        outstanding_fury: stats.outstanding_fury,
+        matured_fury: matured_fury,
+        ust_raised: stats.ust_raised,
+    })
+}
+
+fn query_maturity_histogram(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MaturityHistogramEntry>> {
+    let config = CONFIG.load(deps.storage)?;
+    let buckets =
+        read_maturity_histogram(deps.storage, config.swap_opening_date, start_after, limit)?;
+    Ok(buckets
+        .into_iter()
+        .map(|(day, bucket)| MaturityHistogramEntry {
+            day,
+            pair: bucket.pair,
+            native: bucket.native,
+        })
+        .collect())
 }
 
-fn get_ust_equivalent_to_fury(deps: Deps, fury_count: Uint128) -> StdResult<Uint128> {
+fn query_bond_capacity(deps: Deps, env: Env) -> StdResult<BondCapacityResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
+    Ok(BondCapacityResponse {
+        pair: query_bond_capacity_info(
+            deps,
+            &env,
+            &config,
+            BondType::Pair,
+            &config.pair_fury_reward_wallet,
+        )?,
+        native: query_bond_capacity_info(
+            deps,
+            &env,
+            &config,
+            BondType::Native,
+            &config.native_investment_reward_wallet,
+        )?,
+    })
+}
+
+fn query_bond_capacity_info(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    bond_type: BondType,
+    reward_wallet: &Addr,
+) -> StdResult<BondCapacityInfo> {
+    let bond_capacity = bond_capacity_config(config, bond_type);
+    let usage = load_bond_capacity_usage(deps.storage, config, bond_type, env.block.time)?;
+    Ok(BondCapacityInfo {
+        epoch_start: usage.epoch_start,
+        bonded_in_epoch: usage.bonded_in_epoch,
+        remaining_in_epoch: bond_capacity
+            .max_fury_per_epoch
+            .map(|max_fury_per_epoch| max_fury_per_epoch.saturating_sub(usage.bonded_in_epoch)),
+        total_bonded: usage.total_bonded,
+        remaining_budget: bond_capacity
+            .total_budget
+            .map(|total_budget| total_budget.saturating_sub(usage.total_bonded)),
+        reward_wallet_available: query_reward_wallet_available(
+            deps,
+            config,
+            reward_wallet.to_string(),
+            env.contract.address.to_string(),
+        )?,
+    })
+}
+
+fn get_ust_equivalent_to_fury(
+    deps: Deps,
+    config: &Config,
+    fury_count: Uint128,
+) -> StdResult<Uint128> {
     let pool_rsp: PoolResponse = deps
         .querier
-        .query_wasm_smart(config.pool_pair_address, &Pool {})?;
+        .query_wasm_smart(&config.pool_pair_address, &Pool {})?;
 
     let mut uust_count = Uint128::zero();
     let mut ufury_count = Uint128::zero();
     for asset in pool_rsp.assets {
-        if (asset.info.is_native_token()) {
+        if (asset.info == config.quote_asset) {
             uust_count = asset.amount;
         }
-        if (!asset.info.is_native_token()) {
+        if (is_fury(config, &asset.info)) {
             ufury_count = asset.amount;
         }
     }
@@ -1454,19 +5677,22 @@ fn get_ust_equivalent_to_fury(deps: Deps, fury_count: Uint128) -> StdResult<Uint
     return Ok(ust_equiv_for_fury);
 }
 
-fn get_fury_equivalent_to_ust(deps: Deps, ust_count: Uint128) -> StdResult<Uint128> {
-    let config: Config = CONFIG.load(deps.storage)?;
+fn get_fury_equivalent_to_ust(
+    deps: Deps,
+    config: &Config,
+    ust_count: Uint128,
+) -> StdResult<Uint128> {
     let pool_rsp: PoolResponse = deps
         .querier
-        .query_wasm_smart(config.pool_pair_address, &Pool {})?;
+        .query_wasm_smart(&config.pool_pair_address, &Pool {})?;
 
     let mut uust_count = Uint128::zero();
     let mut ufury_count = Uint128::zero();
     for asset in pool_rsp.assets {
-        if (asset.info.is_native_token()) {
+        if (asset.info == config.quote_asset) {
             uust_count = asset.amount;
         }
-        if (!asset.info.is_native_token()) {
+        if (is_fury(config, &asset.info)) {
             ufury_count = asset.amount;
         }
     }
@@ -1481,8 +5707,68 @@ fn get_fury_equivalent_to_ust(deps: Deps, ust_count: Uint128) -> StdResult<Uint1
     return Ok(fury_equiv_for_ust);
 }
 
-pub fn query_platform_fees(deps: Deps, msg: Binary) -> StdResult<Uint128> {
-    let config = CONFIG.load(deps.storage)?;
+/// Prices the Fury of a bond. When an oracle is configured the TWAP is used instead of the spot
+/// price, with the same price impact the pool would apply, and the bond is rejected if the spot
+/// amount deviates from it by more than max_twap_deviation
+fn get_bond_fury_equivalent_to_ust(
+    deps: Deps,
+    config: &Config,
+    ust_count: Uint128,
+) -> Result<Uint128, ContractError> {
+    let oracle_address = match &config.oracle_address {
+        Some(oracle_address) => oracle_address,
+        None => return Ok(get_fury_equivalent_to_ust(deps, config, ust_count)?),
+    };
+    // The oracle answers with a Uint256, which serializes to the same decimal string as Uint128
+    let twap_linear_amount: Uint128 = deps.querier.query_wasm_smart(
+        oracle_address,
+        &OracleQueryMsg::Consult {
+            token: config.quote_asset.clone(),
+            amount: ust_count,
+        },
+    )?;
+
+    let pool_rsp: PoolResponse = deps
+        .querier
+        .query_wasm_smart(config.pool_pair_address.clone(), &Pool {})?;
+    let mut uust_count = Uint128::zero();
+    for asset in pool_rsp.assets {
+        if asset.info == config.quote_asset {
+            uust_count = asset.amount;
+        }
+    }
+    // Apply the constant product price impact of the pool to the TWAP amount, so that it can be
+    // compared with the simulated spot amount
+    let uust_after_bond = uust_count.checked_add(ust_count).map_err(StdError::from)?;
+    if uust_after_bond.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    let twap_amount = twap_linear_amount.multiply_ratio(uust_count, uust_after_bond);
+
+    if let Some(max_twap_deviation) = config.max_twap_deviation {
+        let spot_amount = get_fury_equivalent_to_ust(deps, config, ust_count)?;
+        let deviation = if spot_amount > twap_amount {
+            spot_amount - twap_amount
+        } else {
+            twap_amount - spot_amount
+        };
+        if deviation > twap_amount * max_twap_deviation {
+            return Err(ContractError::TwapDeviationTooHigh {
+                spot_amount,
+                twap_amount,
+            });
+        }
+    }
+    Ok(twap_amount)
+}
+
+pub fn query_platform_fees(
+    deps: Deps,
+    env: Env,
+    msg: Binary,
+    user_address: Option<String>,
+) -> StdResult<Uint128> {
+    let config = load_message_config(deps.storage, &msg)?;
     let platform_fees_percentage;
     let mut fury_amount_provided = Uint128::zero();
     let mut ust_amount_provided = Uint128::zero();
@@ -1494,6 +5780,106 @@ pub fn query_platform_fees(deps: Deps, msg: Binary) -> StdResult<Uint128> {
         }) => {
             return Ok(Uint128::zero());
         }
+        Ok(ExecuteMsg::UpdateConfig(_)) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::ProposeNewOwner {
+            owner: _,
+            expires_in: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::DropOwnershipProposal {}) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::ClaimOwnership {}) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::AddBondTerm {
+            bond_type: _,
+            bonding_period_in_sec: _,
+            discount_rate: _,
+            vesting_schedule: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::RetireBondTerm { term_id: _ }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::SetPair {
+            pair_id: _,
+            settings: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::RemovePair { pair_id: _ }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::ClaimReferralRewards {}) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::HarvestProtocolLiquidity {}) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::UnstakeProtocolLiquidity {
+            lp_token: _,
+            amount: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::UpdateEarlyAccessAllowlist { add: _, remove: _ }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::RegisterEarlyAccess { cap: _, proof: _ }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::SetPauseStatus {
+            swaps: _,
+            pair_bonding: _,
+            native_bonding: _,
+            claims: _,
+            withdrawals: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::TransferNft {
+            recipient: _,
+            token_id: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::SendNft {
+            contract: _,
+            token_id: _,
+            msg: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::Approve {
+            spender: _,
+            token_id: _,
+            expires: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::Revoke {
+            spender: _,
+            token_id: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::ApproveAll {
+            operator: _,
+            expires: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::RevokeAll { operator: _ }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::CancelPendingRequest { request_id: _ }) => {
+            return Ok(Uint128::zero());
+        }
         Ok(ExecuteMsg::Receive(_)) => {
             return Ok(Uint128::zero());
         }
@@ -1501,13 +5887,16 @@ pub fn query_platform_fees(deps: Deps, msg: Binary) -> StdResult<Uint128> {
             assets,
             slippage_tolerance: _,
             auto_stake: _,
+            term_id: _,
+            pair_id: _,
+            referrer: _,
         }) => {
             platform_fees_percentage = config.platform_fees + config.transaction_fees;
             for asset in assets {
-                if asset.info.is_native_token() {
+                if asset.info == config.quote_asset {
                     ust_amount_provided = asset.amount;
                 }
-                if !asset.info.is_native_token() {
+                if is_fury(&config, &asset.info) {
                     fury_amount_provided = asset.amount;
                 }
             }
@@ -1516,9 +5905,12 @@ pub fn query_platform_fees(deps: Deps, msg: Binary) -> StdResult<Uint128> {
             asset,
             slippage_tolerance: _,
             auto_stake: _,
+            term_id: _,
+            pair_id: _,
+            referrer: _,
         }) => {
             platform_fees_percentage = config.platform_fees + config.transaction_fees;
-            if asset.info.is_native_token() {
+            if asset.info == config.quote_asset {
                 ust_amount_provided = asset.amount;
             }
         }
@@ -1526,7 +5918,18 @@ pub fn query_platform_fees(deps: Deps, msg: Binary) -> StdResult<Uint128> {
             assets: _,
             slippage_tolerance: _,
             auto_stake: _,
+            pair_id: _,
+        }) => {
+            return Ok(Uint128::zero());
+        }
+        Ok(ExecuteMsg::ExecuteSwapOperations {
+            offer_asset: _,
+            operations: _,
+            minimum_receive: _,
+            to: _,
+            pair_id: _,
         }) => {
+            // Taken out of the quote asset returned by the router
             return Ok(Uint128::zero());
         }
         Ok(ExecuteMsg::Swap {
@@ -1534,39 +5937,88 @@ pub fn query_platform_fees(deps: Deps, msg: Binary) -> StdResult<Uint128> {
             belief_price: _,
             max_spread: _,
             to: _,
+            pay_fees_in_fury: _,
+            deduct_fees_from_proceeds: _,
+            pair_id: _,
+            referrer: _,
         }) => {
             platform_fees_percentage =
                 config.platform_fees + config.transaction_fees + config.swap_fees;
-            if offer_asset.info.is_native_token() {
+            if offer_asset.info == config.quote_asset {
                 ust_amount_provided = offer_asset.amount;
             }
-            if !offer_asset.info.is_native_token() {
+            if is_fury(&config, &offer_asset.info) {
                 fury_amount_provided = offer_asset.amount;
             }
         }
         Ok(ExecuteMsg::RewardClaim {
             receiver: _,
             withdrawal_amount,
+            pay_fees_in_fury: _,
         }) => {
             platform_fees_percentage = config.platform_fees + config.transaction_fees;
             fury_amount_provided = withdrawal_amount;
         }
+        Ok(ExecuteMsg::ClaimAllMatured {
+            pay_fees_in_fury: _,
+        }) => {
+            platform_fees_percentage = config.platform_fees + config.transaction_fees;
+            let user_address = user_address.ok_or_else(|| {
+                StdError::generic_err("user_address is required to price a claim")
+            })?;
+            let bonds = read_user_bonds(deps.storage, &user_address)?;
+            for bond in bonds {
+                fury_amount_provided += claimable_amount(&config, &bond, env.block.time);
+            }
+        }
+        Ok(ExecuteMsg::ClaimBond {
+            bond_id,
+            pay_fees_in_fury: _,
+        }) => {
+            platform_fees_percentage = config.platform_fees + config.transaction_fees;
+            let user_address = user_address.ok_or_else(|| {
+                StdError::generic_err("user_address is required to price a claim")
+            })?;
+            let bonds = read_user_bonds(deps.storage, &user_address)?;
+            for bond in bonds {
+                if bond.bond_id == bond_id {
+                    fury_amount_provided = claimable_amount(&config, &bond, env.block.time);
+                }
+            }
+        }
+        Ok(ExecuteMsg::EarlyUnbond {
+            bond_id,
+            pay_fees_in_fury: _,
+        }) => {
+            platform_fees_percentage = config.platform_fees + config.transaction_fees;
+            let user_address = user_address.ok_or_else(|| {
+                StdError::generic_err("user_address is required to price an early unbond")
+            })?;
+            let bonds = read_user_bonds(deps.storage, &user_address)?;
+            for bond in bonds {
+                if bond.bond_id == bond_id {
+                    let (payout, _) = early_unbond_amounts(&config, &bond, env.block.time);
+                    fury_amount_provided = payout;
+                }
+            }
+        }
         Err(err) => {
             return Err(StdError::generic_err(format!("{:?}", err)));
         }
     }
-    let ust_equiv_for_fury = get_ust_equivalent_to_fury(deps, fury_amount_provided)?;
+    let ust_equiv_for_fury = get_ust_equivalent_to_fury(deps, &config, fury_amount_provided)?;
 
     let platform_fee = (ust_equiv_for_fury.checked_add(ust_amount_provided)?)
         .checked_mul(platform_fees_percentage)?
         .checked_div(Uint128::from(HUNDRED_PERCENT))?;
     let pf_asset = Asset {
-        info: AssetInfo::NativeToken {
-            denom: String::from("uusd"),
-        },
+        info: config.quote_asset.clone(),
         amount: platform_fee,
     };
 
     let tax_on_pf = pf_asset.compute_tax(&deps.querier)?;
     return Ok(platform_fee.checked_add(tax_on_pf)?);
 }
+
+#[cfg(test)]
+mod tests;
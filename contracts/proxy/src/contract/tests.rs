@@ -0,0 +1,2354 @@
+use super::*;
+use crate::migration::{BondedRewardsDetailsV100, ConfigV100};
+use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
+use crate::state::{BondStats, ReferralRewards};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{attr, coin, OwnedDeps, SubMsgExecutionResponse};
+
+const ADMIN: &str = "admin";
+const USER: &str = "user";
+const FURY_TOKEN: &str = "fury_token";
+const PAIR: &str = "pair";
+const LP_TOKEN: &str = "lp_token";
+const PAIR_REWARD_WALLET: &str = "pair_reward_wallet";
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        admin_address: ADMIN.to_string(),
+        custom_token_address: FURY_TOKEN.to_string(),
+        pair_discount_rate: 1000,
+        pair_bonding_period_in_sec: 100,
+        pair_fury_reward_wallet: PAIR_REWARD_WALLET.to_string(),
+        pair_lp_tokens_holder: "lp_holder".to_string(),
+        native_discount_rate: 500,
+        native_bonding_period_in_sec: 200,
+        native_investment_reward_wallet: "native_reward_wallet".to_string(),
+        native_investment_receive_wallet: "native_receive_wallet".to_string(),
+        authorized_liquidity_provider: "liquidity_provider".to_string(),
+        swap_opening_date: Uint64::zero(),
+        pool_pair_address: Some(PAIR.to_string()),
+        platform_fees_collector_wallet: "fee_collector".to_string(),
+        platform_fees: Uint128::new(100),
+        transaction_fees: Uint128::new(30),
+        swap_fees: Uint128::zero(),
+        max_bonding_limit_per_user: 10,
+        pair_vesting_schedule: None,
+        native_vesting_schedule: None,
+        oracle_address: None,
+        max_twap_deviation: None,
+        pair_bond_capacity: None,
+        native_bond_capacity: None,
+        bonds_transferable: None,
+        early_unbond_penalty_rate: None,
+        early_unbond_penalty_destination: None,
+        quote_asset: None,
+        fury_fee_discount: None,
+        fee_collectors: None,
+        maker_address: None,
+        router_address: None,
+        guardian_address: None,
+        referral_fee_share: None,
+        early_access_window_in_sec: None,
+        early_access_merkle_root: None,
+        swap_limits: None,
+        generator_address: None,
+    }
+}
+
+fn instantiate_proxy(msg: InstantiateMsg) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_pool(PAIR, fury_ust_pool(1000, 1000), Uint128::new(1000));
+    instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), msg).unwrap();
+    let mut config = CONFIG.load(&deps.storage).unwrap();
+    config.liquidity_token = Addr::unchecked(LP_TOKEN);
+    CONFIG.save(&mut deps.storage, &config).unwrap();
+    deps
+}
+
+fn fury_ust_pool(fury_amount: u128, ust_amount: u128) -> [Asset; 2] {
+    [
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked(FURY_TOKEN),
+            },
+            amount: Uint128::new(fury_amount),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(ust_amount),
+        },
+    ]
+}
+
+fn fury_transfer(recipient: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: FURY_TOKEN.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        })
+        .unwrap(),
+        funds: vec![],
+    })
+}
+
+/// Creates a bond request of USER holding 1000uusd, 100 Fury of the user and 200 Fury of the
+/// pair reward wallet, dispatched as submessage 7 at the given step
+fn pending_bond_request(storage: &mut dyn Storage, status: PendingRequestStatus) -> u64 {
+    let request_id = create_pending_request(
+        storage,
+        USER.to_string(),
+        vec![coin(1000, "uusd")],
+        Uint128::new(100),
+        None,
+        None,
+    )
+    .unwrap();
+    let mut request = advance_pending_request(storage, request_id, 7, status).unwrap();
+    request.treasury_address = Some(Addr::unchecked(PAIR_REWARD_WALLET));
+    request.treasury_fury_amount = Uint128::new(200);
+    PENDING_REQUESTS
+        .save(storage, U64Key::new(request_id), &request)
+        .unwrap();
+    SUB_MESSAGE_DETAILS
+        .save(
+            storage,
+            "7".to_string(),
+            &SubMessageDetails {
+                sub_req_id: "7".to_string(),
+                request_type: SubMessageType::ProvideLiquiditySubMsg,
+                next_action: SubMessageNextAction::CompletePendingRequest,
+                sub_message_payload: Binary::default(),
+                funds: vec![],
+                user_address: USER.to_string(),
+                is_fury_provided: true,
+                term_id: None,
+                request_id,
+            },
+        )
+        .unwrap();
+    request_id
+}
+
+fn rollback_messages(status: PendingRequestStatus) -> Vec<CosmosMsg> {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id = pending_bond_request(&mut deps.storage, status);
+    let request = PENDING_REQUESTS
+        .load(&deps.storage, U64Key::new(request_id))
+        .unwrap();
+    let res = rollback_pending_request(deps.as_mut(), mock_env(), request).unwrap();
+
+    assert!(PENDING_REQUESTS
+        .may_load(&deps.storage, U64Key::new(request_id))
+        .unwrap()
+        .is_none());
+    assert!(SUB_MESSAGE_DETAILS
+        .may_load(&deps.storage, "7".to_string())
+        .unwrap()
+        .is_none());
+    res.messages
+        .into_iter()
+        .map(|sub_msg| sub_msg.msg)
+        .collect()
+}
+
+fn refund_msg() -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: USER.to_string(),
+        amount: vec![coin(1000, "uusd")],
+    })
+}
+
+#[test]
+fn pending_requests_are_created_awaiting_the_user_transfer() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    for expected_id in 1..=2 {
+        let request_id = create_pending_request(
+            &mut deps.storage,
+            USER.to_string(),
+            vec![],
+            Uint128::new(100),
+            Some("fury_ust".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(request_id, expected_id);
+    }
+
+    let request = PENDING_REQUESTS
+        .load(&deps.storage, U64Key::new(2))
+        .unwrap();
+    assert_eq!(request.status, PendingRequestStatus::UserTransfer);
+    assert_eq!(request.user_fury_amount, Uint128::new(100));
+    assert_eq!(request.pair_id, Some("fury_ust".to_string()));
+    assert_eq!(request.bond_id, None);
+}
+
+#[test]
+fn advance_pending_request_moves_to_the_dispatched_step() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id = create_pending_request(
+        &mut deps.storage,
+        USER.to_string(),
+        vec![],
+        Uint128::zero(),
+        None,
+        None,
+    )
+    .unwrap();
+
+    let request = advance_pending_request(
+        &mut deps.storage,
+        request_id,
+        3,
+        PendingRequestStatus::Allowance,
+    )
+    .unwrap();
+    assert_eq!(request.status, PendingRequestStatus::Allowance);
+    assert_eq!(request.sub_req_id, 3);
+    assert_eq!(
+        PENDING_REQUESTS
+            .load(&deps.storage, U64Key::new(request_id))
+            .unwrap(),
+        request
+    );
+
+    let err = advance_pending_request(
+        &mut deps.storage,
+        request_id + 1,
+        4,
+        PendingRequestStatus::Allowance,
+    )
+    .unwrap_err();
+    assert!(matches!(err, StdError::NotFound { .. }));
+}
+
+#[test]
+fn pending_request_status_reads_the_previous_names() {
+    let status: PendingRequestStatus =
+        from_binary(&to_binary("awaiting_allowance").unwrap()).unwrap();
+    assert_eq!(status, PendingRequestStatus::Allowance);
+}
+
+#[test]
+fn rollback_before_the_user_transfer_only_refunds_native_funds() {
+    assert_eq!(
+        rollback_messages(PendingRequestStatus::UserTransfer),
+        vec![refund_msg()]
+    );
+}
+
+#[test]
+fn rollback_during_the_treasury_transfer_returns_the_user_fury() {
+    assert_eq!(
+        rollback_messages(PendingRequestStatus::TreasuryTransfer),
+        vec![fury_transfer(USER, 100), refund_msg()]
+    );
+}
+
+#[test]
+fn rollback_during_the_allowance_returns_the_user_and_treasury_fury() {
+    assert_eq!(
+        rollback_messages(PendingRequestStatus::Allowance),
+        vec![
+            fury_transfer(USER, 100),
+            fury_transfer(PAIR_REWARD_WALLET, 200),
+            refund_msg()
+        ]
+    );
+}
+
+#[test]
+fn rollback_during_provide_liquidity_also_revokes_the_pair_allowance() {
+    assert_eq!(
+        rollback_messages(PendingRequestStatus::ProvideLiquidity),
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: FURY_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::DecreaseAllowance {
+                    spender: PAIR.to_string(),
+                    amount: Uint128::new(100),
+                    expires: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            fury_transfer(USER, 100),
+            fury_transfer(PAIR_REWARD_WALLET, 200),
+            refund_msg()
+        ]
+    );
+}
+
+#[test]
+fn rollback_refunds_native_funds_net_of_tax() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    deps.querier
+        .with_tax(Decimal::percent(1), &[("uusd", Uint128::new(1000000))]);
+    let request_id = pending_bond_request(&mut deps.storage, PendingRequestStatus::UserTransfer);
+    let request = PENDING_REQUESTS
+        .load(&deps.storage, U64Key::new(request_id))
+        .unwrap();
+
+    let res = rollback_pending_request(deps.as_mut(), mock_env(), request).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: USER.to_string(),
+            amount: vec![coin(990, "uusd")],
+        })
+    );
+}
+
+#[test]
+fn rollback_removes_the_recorded_bond() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id =
+        pending_bond_request(&mut deps.storage, PendingRequestStatus::ProvideLiquidity);
+    let bond = BondedRewardsDetails {
+        bond_id: 1,
+        user_address: USER.to_string(),
+        bond_type: BondType::Pair,
+        bonded_amount: Uint128::new(300),
+        bonding_period: 100,
+        bonding_start_timestamp: mock_env().block.time,
+        ..Default::default()
+    };
+    BONDED_REWARDS_DETAILS
+        .save(&mut deps.storage, (USER, U64Key::new(1)), &bond)
+        .unwrap();
+    BOND_OWNERS
+        .save(&mut deps.storage, U64Key::new(1), &USER.to_string())
+        .unwrap();
+    let mut request = PENDING_REQUESTS
+        .load(&deps.storage, U64Key::new(request_id))
+        .unwrap();
+    request.bond_id = Some(1);
+
+    rollback_pending_request(deps.as_mut(), mock_env(), request).unwrap();
+    assert!(BONDED_REWARDS_DETAILS
+        .may_load(&deps.storage, (USER, U64Key::new(1)))
+        .unwrap()
+        .is_none());
+    assert!(BOND_OWNERS
+        .may_load(&deps.storage, U64Key::new(1))
+        .unwrap()
+        .is_none());
+}
+
+/// Records 100uusd of platform fees on the request, 10uusd of which are owed to a referrer
+fn charge_platform_fees(storage: &mut dyn Storage, request_id: u64) {
+    let mut request = PENDING_REQUESTS
+        .load(storage, U64Key::new(request_id))
+        .unwrap();
+    request.referrer = Some(Addr::unchecked("referrer"));
+    request.platform_fees = Uint128::new(100);
+    request.referral_fees = Uint128::new(10);
+    PENDING_REQUESTS
+        .save(storage, U64Key::new(request_id), &request)
+        .unwrap();
+}
+
+#[test]
+fn rollback_refunds_the_platform_fees_without_crediting_the_referrer() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id = pending_bond_request(&mut deps.storage, PendingRequestStatus::UserTransfer);
+    charge_platform_fees(&mut deps.storage, request_id);
+    let request = PENDING_REQUESTS
+        .load(&deps.storage, U64Key::new(request_id))
+        .unwrap();
+
+    let res = rollback_pending_request(deps.as_mut(), mock_env(), request).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: USER.to_string(),
+            amount: vec![coin(1100, "uusd")],
+        })
+    );
+    assert!(!REFERRAL_REWARDS.has(&deps.storage, "referrer"));
+}
+
+#[test]
+fn platform_fees_are_collected_and_the_referrer_credited_once_the_request_completes() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id =
+        pending_bond_request(&mut deps.storage, PendingRequestStatus::ProvideLiquidity);
+    charge_platform_fees(&mut deps.storage, request_id);
+    let mut details = SUB_MESSAGE_DETAILS
+        .load(&deps.storage, "7".to_string())
+        .unwrap();
+    details.sub_message_payload = to_binary(&PairExecuteMsg::ProvideLiquidity {
+        assets: fury_ust_pool(100, 100),
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: Some("lp_holder".to_string()),
+    })
+    .unwrap();
+    SUB_MESSAGE_DETAILS
+        .save(&mut deps.storage, "7".to_string(), &details)
+        .unwrap();
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 7,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "fee_collector".to_string(),
+            amount: vec![coin(90, "uusd")],
+        }))]
+    );
+    assert!(!PENDING_REQUESTS.has(&deps.storage, U64Key::new(request_id)));
+    let credit = vec![Asset {
+        info: uusd(),
+        amount: Uint128::new(10),
+    }];
+    assert_eq!(
+        REFERRAL_REWARDS.load(&deps.storage, "referrer").unwrap(),
+        ReferralRewards {
+            claimable: credit.clone(),
+            total_earned: credit,
+        }
+    );
+}
+
+#[test]
+fn failed_submessage_rolls_back_its_pending_request() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id = pending_bond_request(&mut deps.storage, PendingRequestStatus::Allowance);
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 7,
+            result: ContractResult::Err("pair failed".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert!(res
+        .attributes
+        .contains(&attr("action", "rollback_pending_request")));
+    assert!(res.attributes.contains(&attr("error", "pair failed")));
+    assert!(PENDING_REQUESTS
+        .may_load(&deps.storage, U64Key::new(request_id))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn failed_submessage_without_pending_request_is_an_error() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 7,
+            result: ContractResult::Err("pair failed".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Received error: \"pair failed\""
+    );
+}
+
+#[test]
+fn successful_submessage_is_not_rolled_back() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id = pending_bond_request(&mut deps.storage, PendingRequestStatus::Allowance);
+    SUB_MESSAGE_DETAILS.remove(&mut deps.storage, "7".to_string());
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 7,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert!(PENDING_REQUESTS
+        .may_load(&deps.storage, U64Key::new(request_id))
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn only_the_admin_can_cancel_a_pending_request() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let request_id = pending_bond_request(&mut deps.storage, PendingRequestStatus::Allowance);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        ExecuteMsg::CancelPendingRequest { request_id },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::CancelPendingRequest {
+            request_id: request_id + 1,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PendingRequestNotFound {
+            request_id: request_id + 1
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::CancelPendingRequest { request_id },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|sub_msg| sub_msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            fury_transfer(USER, 100),
+            fury_transfer(PAIR_REWARD_WALLET, 200),
+            refund_msg()
+        ]
+    );
+    assert!(PENDING_REQUESTS
+        .may_load(&deps.storage, U64Key::new(request_id))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn fees_adding_up_past_the_whole_amount_are_rejected() {
+    for platform_fees in [Uint128::new(9970), Uint128::MAX] {
+        let err = instantiate(
+            mock_dependencies(&[]).as_mut(),
+            mock_env(),
+            mock_info(ADMIN, &[]),
+            InstantiateMsg {
+                platform_fees,
+                ..instantiate_msg()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidFees {});
+    }
+}
+
+fn twap_proxy(price: Decimal) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = instantiate_proxy(InstantiateMsg {
+        oracle_address: Some("oracle".to_string()),
+        max_twap_deviation: Some(Decimal::percent(5)),
+        ..instantiate_msg()
+    });
+    deps.querier.with_oracle("oracle", price);
+    deps
+}
+
+#[test]
+fn twap_price_gets_the_same_price_impact_as_the_spot_price() {
+    let deps = twap_proxy(Decimal::one());
+    let config = CONFIG.load(&deps.storage).unwrap();
+
+    // 100 uusd are worth 100 Fury at the TWAP, and 90 once they move the 1000/1000 pool
+    assert_eq!(
+        get_bond_fury_equivalent_to_ust(deps.as_ref(), &config, Uint128::new(100)).unwrap(),
+        Uint128::new(90)
+    );
+    assert_eq!(
+        get_fury_equivalent_to_ust(deps.as_ref(), &config, Uint128::new(100)).unwrap(),
+        Uint128::new(91)
+    );
+}
+
+#[test]
+fn bond_is_rejected_when_the_spot_price_deviates_from_the_twap() {
+    let deps = twap_proxy(Decimal::percent(50));
+    let config = CONFIG.load(&deps.storage).unwrap();
+
+    let err =
+        get_bond_fury_equivalent_to_ust(deps.as_ref(), &config, Uint128::new(100)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TwapDeviationTooHigh {
+            spot_amount: Uint128::new(91),
+            twap_amount: Uint128::new(45),
+        }
+    );
+}
+
+fn issue_bond(storage: &mut dyn Storage, owner: &str, bond_id: u64) -> BondedRewardsDetails {
+    let bond = BondedRewardsDetails {
+        bond_id,
+        user_address: owner.to_string(),
+        bond_type: BondType::Pair,
+        bonded_amount: Uint128::new(300),
+        bonding_period: 100,
+        bonding_start_timestamp: mock_env().block.time,
+        ..Default::default()
+    };
+    record_bond_issued(storage, &bond, Uint128::zero()).unwrap();
+    BONDED_REWARDS_DETAILS
+        .save(storage, (owner, U64Key::new(bond_id)), &bond)
+        .unwrap();
+    BOND_OWNERS
+        .save(storage, U64Key::new(bond_id), &owner.to_string())
+        .unwrap();
+    bond
+}
+
+fn transferable_bonds_proxy() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = instantiate_proxy(InstantiateMsg {
+        bonds_transferable: Some(true),
+        ..instantiate_msg()
+    });
+    issue_bond(&mut deps.storage, USER, 1);
+    deps
+}
+
+fn query_owner(deps: Deps, include_expired: bool) -> OwnerOfResponse {
+    from_binary(
+        &query(
+            deps,
+            mock_env(),
+            QueryMsg::OwnerOf {
+                token_id: "1".to_string(),
+                include_expired: Some(include_expired),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn approved_spender_can_transfer_a_bond_once() {
+    let mut deps = transferable_bonds_proxy();
+    let transfer = ExecuteMsg::TransferNft {
+        recipient: "buyer".to_string(),
+        token_id: "1".to_string(),
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("market", &[]),
+        transfer.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "1".to_string(),
+            expires: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_owner(deps.as_ref(), false).approvals,
+        vec![Approval {
+            spender: "market".to_string(),
+            expires: Expiration::Never {},
+        }]
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("market", &[]),
+        transfer.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_owner(deps.as_ref(), true),
+        OwnerOfResponse {
+            owner: "buyer".to_string(),
+            approvals: vec![],
+        }
+    );
+
+    // The approval was given by the previous owner
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("market", &[]),
+        transfer,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn expired_and_revoked_approvals_cannot_transfer() {
+    let mut deps = transferable_bonds_proxy();
+    let env = mock_env();
+    let expires = Expiration::AtHeight(env.block.height + 1);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "1".to_string(),
+            expires: Some(expires),
+        },
+    )
+    .unwrap();
+
+    let mut later = env.clone();
+    later.block.height += 1;
+    let err = execute(
+        deps.as_mut(),
+        later.clone(),
+        mock_info("market", &[]),
+        ExecuteMsg::TransferNft {
+            recipient: "buyer".to_string(),
+            token_id: "1".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res: OwnerOfResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            later.clone(),
+            QueryMsg::OwnerOf {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.approvals.is_empty());
+
+    let err = execute(
+        deps.as_mut(),
+        later,
+        mock_info(USER, &[]),
+        ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "1".to_string(),
+            expires: Some(expires),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ApprovalExpired {});
+
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &[]),
+        ExecuteMsg::Revoke {
+            spender: "market".to_string(),
+            token_id: "1".to_string(),
+        },
+    )
+    .unwrap();
+    assert!(query_owner(deps.as_ref(), true).approvals.is_empty());
+}
+
+#[test]
+fn operator_can_approve_and_transfer_all_bonds_of_the_owner() {
+    let mut deps = transferable_bonds_proxy();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        ExecuteMsg::ApproveAll {
+            operator: "operator".to_string(),
+            expires: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "1".to_string(),
+            expires: None,
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        ExecuteMsg::RevokeAll {
+            operator: "operator".to_string(),
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::TransferNft {
+            recipient: "buyer".to_string(),
+            token_id: "1".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn bond_is_described_as_a_cw721_token() {
+    let deps = transferable_bonds_proxy();
+    let bond = BONDED_REWARDS_DETAILS
+        .load(&deps.storage, (USER, U64Key::new(1)))
+        .unwrap();
+
+    let res: AllNftInfoResponse<BondedRewardsDetails> = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AllNftInfo {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        AllNftInfoResponse {
+            access: OwnerOfResponse {
+                owner: USER.to_string(),
+                approvals: vec![],
+            },
+            info: NftInfoResponse {
+                token_uri: None,
+                extension: bond,
+            },
+        }
+    );
+
+    let res: ContractInfoResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).unwrap()).unwrap();
+    assert_eq!(res.symbol, BOND_TOKEN_SYMBOL);
+}
+
+#[test]
+fn num_tokens_counts_outstanding_bonds() {
+    let mut deps = transferable_bonds_proxy();
+    let bond = issue_bond(&mut deps.storage, "other", 2);
+    let num_tokens = |deps: Deps| -> u64 {
+        let res: NumTokensResponse =
+            from_binary(&query(deps, mock_env(), QueryMsg::NumTokens {}).unwrap()).unwrap();
+        res.count
+    };
+    assert_eq!(num_tokens(deps.as_ref()), 2);
+
+    remove_bond(&mut deps.storage, "other".to_string(), 2).unwrap();
+    record_bond_released(&mut deps.storage, &bond, bond.bonded_amount, true).unwrap();
+    assert_eq!(num_tokens(deps.as_ref()), 1);
+}
+
+fn proxy_config() -> Config {
+    CONFIG
+        .load(&instantiate_proxy(instantiate_msg()).storage)
+        .unwrap()
+}
+
+fn early_unbond_config() -> Config {
+    Config {
+        early_unbond_penalty_rate: Some(5000),
+        ..proxy_config()
+    }
+}
+
+fn bond_started_at(start: Timestamp, vesting_schedule: VestingSchedule) -> BondedRewardsDetails {
+    BondedRewardsDetails {
+        bond_id: 1,
+        user_address: USER.to_string(),
+        bond_type: BondType::Pair,
+        bonded_amount: Uint128::new(1000),
+        bonding_period: 100,
+        bonding_start_timestamp: start,
+        vesting_schedule,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn early_unbond_penalty_shrinks_until_maturity() {
+    let config = early_unbond_config();
+    let start = mock_env().block.time;
+    let bond = bond_started_at(start, VestingSchedule::Cliff);
+
+    assert_eq!(
+        early_unbond_amounts(&config, &bond, start),
+        (Uint128::new(500), Uint128::new(500))
+    );
+    assert_eq!(
+        early_unbond_amounts(&config, &bond, start.plus_seconds(50)),
+        (Uint128::new(750), Uint128::new(250))
+    );
+    assert_eq!(
+        early_unbond_amounts(&config, &bond, start.plus_seconds(100)),
+        (Uint128::new(1000), Uint128::zero())
+    );
+}
+
+#[test]
+fn early_unbond_penalty_only_applies_to_the_unvested_part() {
+    let config = early_unbond_config();
+    let start = mock_env().block.time;
+    let mut bond = bond_started_at(start, VestingSchedule::Linear);
+    bond.claimed_amount = Uint128::new(200);
+
+    // 500 vested of which 200 claimed, half the penalty rate on the 500 unvested
+    assert_eq!(
+        early_unbond_amounts(&config, &bond, start.plus_seconds(50)),
+        (Uint128::new(675), Uint128::new(125))
+    );
+}
+
+#[test]
+fn early_unbond_of_a_pre_opening_bond_counts_from_the_swap_opening() {
+    let mut config = early_unbond_config();
+    config.swap_opening_date = mock_env().block.time.plus_seconds(1000);
+    let bond = bond_started_at(Timestamp::from_seconds(0), VestingSchedule::Cliff);
+
+    assert_eq!(
+        early_unbond_amounts(&config, &bond, mock_env().block.time),
+        (Uint128::new(500), Uint128::new(500))
+    );
+    assert_eq!(
+        early_unbond_amounts(&config, &bond, config.swap_opening_date.plus_seconds(100)),
+        (Uint128::new(1000), Uint128::zero())
+    );
+}
+
+#[test]
+fn early_unbond_is_rejected_when_disabled_or_for_unknown_bonds() {
+    let msg = InstantiateMsg {
+        platform_fees: Uint128::zero(),
+        transaction_fees: Uint128::zero(),
+        ..instantiate_msg()
+    };
+    let early_unbond = ExecuteMsg::EarlyUnbond {
+        bond_id: 1,
+        pay_fees_in_fury: None,
+    };
+
+    let mut deps = instantiate_proxy(msg.clone());
+    issue_bond(&mut deps.storage, USER, 1);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        early_unbond.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EarlyUnbondDisabled {});
+
+    let mut deps = instantiate_proxy(InstantiateMsg {
+        early_unbond_penalty_rate: Some(5000),
+        ..msg
+    });
+    issue_bond(&mut deps.storage, USER, 1);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other", &[]),
+        early_unbond.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BondNotFound { bond_id: 1 });
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        early_unbond,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[res.messages.len() - 1].msg,
+        fury_transfer(PAIR_REWARD_WALLET, 150)
+    );
+    assert!(res.attributes.contains(&attr("penalty", "150")));
+}
+
+fn fee_collectors(shares: &[u16]) -> Vec<FeeCollector> {
+    shares
+        .iter()
+        .enumerate()
+        .map(|(i, share)| FeeCollector {
+            recipient: FeeRecipient::Address {
+                address: format!("collector{}", i),
+            },
+            share: *share,
+        })
+        .collect()
+}
+
+#[test]
+fn fees_go_to_the_platform_wallet_without_fee_collectors() {
+    let config = proxy_config();
+    assert_eq!(
+        split_fees(&config, Uint128::new(100)),
+        vec![("fee_collector".to_string(), Uint128::new(100))]
+    );
+    assert!(split_fees(&config, Uint128::zero()).is_empty());
+}
+
+#[test]
+fn last_fee_collector_gets_the_rounding_remainder() {
+    let mut config = proxy_config();
+    config.fee_collectors = fee_collectors(&[3333, 3333, 3334]);
+
+    assert_eq!(
+        split_fees(&config, Uint128::new(100)),
+        vec![
+            ("collector0".to_string(), Uint128::new(33)),
+            ("collector1".to_string(), Uint128::new(33)),
+            ("collector2".to_string(), Uint128::new(34)),
+        ]
+    );
+    // Shares rounding down to zero are skipped, nothing is lost
+    assert_eq!(
+        split_fees(&config, Uint128::new(2)),
+        vec![("collector2".to_string(), Uint128::new(2))]
+    );
+}
+
+#[test]
+fn maker_share_falls_back_to_the_platform_wallet() {
+    let mut config = proxy_config();
+    config.fee_collectors = vec![
+        FeeCollector {
+            recipient: FeeRecipient::Maker,
+            share: 5000,
+        },
+        fee_collectors(&[5000]).remove(0),
+    ];
+    assert_eq!(
+        split_fees(&config, Uint128::new(10)),
+        vec![
+            ("fee_collector".to_string(), Uint128::new(5)),
+            ("collector0".to_string(), Uint128::new(5)),
+        ]
+    );
+
+    config.maker_address = Some(Addr::unchecked("maker"));
+    assert_eq!(
+        split_fees(&config, Uint128::new(10))[0],
+        ("maker".to_string(), Uint128::new(5))
+    );
+}
+
+#[test]
+fn fee_collector_shares_must_add_up_to_hundred_percent() {
+    let mut deps = mock_dependencies(&[]);
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        InstantiateMsg {
+            fee_collectors: Some(fee_collectors(&[5000, 4000])),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFeeCollectors {});
+}
+
+fn fury_hook(sender: &str, amount: u128, msg: &ProxyCw20HookMsg) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(msg).unwrap(),
+    })
+}
+
+fn saved_sub_message(storage: &dyn Storage) -> SubMessageDetails {
+    let sub_req_id = SUB_REQ_ID.load(storage).unwrap();
+    SUB_MESSAGE_DETAILS
+        .load(storage, sub_req_id.to_string())
+        .unwrap()
+}
+
+#[test]
+fn fury_sold_through_the_hook_pays_fees_out_of_the_proceeds() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let swap = ProxyCw20HookMsg::Swap {
+        belief_price: None,
+        max_spread: Some(Decimal::percent(1)),
+        to: None,
+        pair_id: None,
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(FURY_TOKEN, &[]),
+        fury_hook(USER, 100, &swap),
+    )
+    .unwrap();
+    // The proxy holds the sent Fury and sells all of it to itself
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: FURY_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: PAIR.to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&Cw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: Some(Decimal::percent(1)),
+                    to: Some(MOCK_CONTRACT_ADDR.to_string()),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let sub_message = saved_sub_message(&deps.storage);
+    assert_eq!(
+        sub_message.next_action,
+        SubMessageNextAction::ForwardSwapProceeds
+    );
+    assert_eq!(sub_message.user_address, USER);
+    let details: SwapProceedsDetails = from_binary(&sub_message.sub_message_payload).unwrap();
+    assert_eq!(details.receiver, USER);
+    assert_eq!(details.offer_amount, Uint128::new(100));
+    assert_eq!(
+        details.ask_asset_info,
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string()
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_token", &[]),
+        fury_hook(USER, 100, &swap),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+fn provide_liquidity_hook(fury_amount: u128) -> ProxyCw20HookMsg {
+    ProxyCw20HookMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked(FURY_TOKEN),
+                },
+                amount: Uint128::new(fury_amount),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("quote_token"),
+                },
+                amount: Uint128::new(50),
+            },
+        ],
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+        pair_id: None,
+    }
+}
+
+#[test]
+fn sent_fury_is_provided_as_liquidity_of_a_cw20_quoted_pair() {
+    let mut deps = instantiate_proxy(InstantiateMsg {
+        quote_asset: Some(AssetInfo::Token {
+            contract_addr: Addr::unchecked("quote_token"),
+        }),
+        ..instantiate_msg()
+    });
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(FURY_TOKEN, &[]),
+        fury_hook(USER, 100, &provide_liquidity_hook(100)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(FURY_TOKEN, &[]),
+        fury_hook("liquidity_provider", 90, &provide_liquidity_hook(100)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::FuryAmountMismatch {
+            required: Uint128::new(100),
+            received: Uint128::new(90),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(FURY_TOKEN, &[]),
+        fury_hook("liquidity_provider", 100, &provide_liquidity_hook(100)),
+    )
+    .unwrap();
+    // No transfer from the provider, the pair is approved on the Fury held by the proxy
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: FURY_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                spender: PAIR.to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let request = PENDING_REQUESTS
+        .load(&deps.storage, U64Key::new(1))
+        .unwrap();
+    assert_eq!(request.status, PendingRequestStatus::Allowance);
+    assert_eq!(request.user_address, "liquidity_provider");
+    assert_eq!(request.user_fury_amount, Uint128::new(100));
+}
+
+#[test]
+fn liquidity_of_a_native_quoted_pair_cannot_be_sent_through_the_hook() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(FURY_TOKEN, &[]),
+        fury_hook("liquidity_provider", 100, &provide_liquidity_hook(100)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NativeQuoteAssetNotSupported {});
+    assert!(read_pending_requests(&deps.storage, None, None)
+        .unwrap()
+        .is_empty());
+}
+
+fn pair_event(contract_address: &str, attributes: &[(&str, &str)]) -> Event {
+    Event::new("wasm")
+        .add_attribute("_contract_address", contract_address)
+        .add_attributes(attributes.iter().copied())
+}
+
+fn swap_events(return_amount: &str) -> Vec<Event> {
+    vec![
+        // A token the pair called along the way cannot stand in for the pair
+        pair_event(
+            "fake_token",
+            &[
+                ("return_amount", "999999"),
+                ("tax_amount", "0"),
+                ("spread_amount", "0"),
+            ],
+        ),
+        pair_event(
+            PAIR,
+            &[
+                ("return_amount", return_amount),
+                ("tax_amount", "10"),
+                ("spread_amount", "5"),
+            ],
+        ),
+    ]
+}
+
+fn swap_proceeds_details(platform_fees: u128) -> SwapProceedsDetails {
+    SwapProceedsDetails {
+        receiver: USER.to_string(),
+        offer_amount: Uint128::new(1000),
+        ask_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        platform_fees: Uint128::new(platform_fees),
+        belief_price: None,
+        max_spread: None,
+        pair_id: None,
+        referrer: None,
+    }
+}
+
+#[test]
+fn swap_proceeds_are_forwarded_net_of_platform_fees() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+
+    let res = forward_swap_proceeds(
+        deps.as_mut(),
+        swap_proceeds_details(20),
+        &swap_events("1000"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|sub_msg| sub_msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: USER.to_string(),
+                amount: vec![coin(970, "uusd")],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee_collector".to_string(),
+                amount: vec![coin(20, "uusd")],
+            }),
+        ]
+    );
+    assert!(res.attributes.contains(&attr("net_amount", "970")));
+}
+
+#[test]
+fn swap_proceeds_must_cover_the_platform_fees() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let err = forward_swap_proceeds(
+        deps.as_mut(),
+        swap_proceeds_details(990),
+        &swap_events("1000"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::FeesExceedProceeds {
+            fees: Uint128::new(990),
+            proceeds: Uint128::new(990),
+        }
+    );
+}
+
+#[test]
+fn max_spread_applies_to_the_net_swap_proceeds() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let details = SwapProceedsDetails {
+        belief_price: Some(Decimal::one()),
+        max_spread: Some(Decimal::percent(5)),
+        ..swap_proceeds_details(20)
+    };
+    forward_swap_proceeds(deps.as_mut(), details.clone(), &swap_events("1000")).unwrap();
+
+    // 1000 expected at the belief price, 940 left after tax and fees
+    let err = forward_swap_proceeds(
+        deps.as_mut(),
+        SwapProceedsDetails {
+            platform_fees: Uint128::new(50),
+            ..details
+        },
+        &swap_events("1000"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
+
+    // Without a belief price the tax and fees count as spread: 5 + 50 out of 1005
+    let err = forward_swap_proceeds(
+        deps.as_mut(),
+        SwapProceedsDetails {
+            max_spread: Some(Decimal::percent(5)),
+            ..swap_proceeds_details(40)
+        },
+        &swap_events("1000"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
+}
+
+#[test]
+fn swap_proceeds_are_only_read_from_the_pair_event() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let err = forward_swap_proceeds(
+        deps.as_mut(),
+        swap_proceeds_details(20),
+        &swap_events("1000")[..1],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("return_amount missing in reply"))
+    );
+
+    let err = reply_event_amount(&swap_events("1e3"), PAIR, "return_amount").unwrap_err();
+    assert_eq!(err, StdError::generic_err("Invalid return_amount in reply"));
+}
+
+fn uusd() -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    }
+}
+
+fn luna_to_fury_route() -> Vec<SwapOperation> {
+    vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            ask_asset_info: uusd(),
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: uusd(),
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked(FURY_TOKEN),
+            },
+        },
+    ]
+}
+
+fn swap_luna_for_fury(minimum_receive: u128) -> ExecuteMsg {
+    ExecuteMsg::ExecuteSwapOperations {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::new(100),
+        },
+        operations: luna_to_fury_route(),
+        minimum_receive: Uint128::new(minimum_receive),
+        to: None,
+        pair_id: None,
+    }
+}
+
+fn router_proxy() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    instantiate_proxy(InstantiateMsg {
+        router_address: Some("router".to_string()),
+        ..instantiate_msg()
+    })
+}
+
+#[test]
+fn route_to_fury_is_sent_through_the_router_to_the_proxy() {
+    let mut deps = router_proxy();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[coin(100, "uluna")]),
+        swap_luna_for_fury(40),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "router".to_string(),
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations: luna_to_fury_route()[..1].to_vec(),
+                minimum_receive: None,
+                to: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            })
+            .unwrap(),
+            funds: vec![coin(100, "uluna")],
+        })
+    );
+    let details: RouteSwapDetails =
+        from_binary(&saved_sub_message(&deps.storage).sub_message_payload).unwrap();
+    assert_eq!(details.receiver, USER);
+    assert_eq!(details.minimum_receive, Uint128::new(40));
+}
+
+#[test]
+fn route_to_fury_requires_a_minimum_receive_and_only_the_offer_as_funds() {
+    let mut deps = router_proxy();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[coin(100, "uluna")]),
+        swap_luna_for_fury(0),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinimumReceiveRequired {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[coin(100, "uluna"), coin(5, "uusd")]),
+        swap_luna_for_fury(40),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnexpectedFunds {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[coin(90, "uluna")]),
+        swap_luna_for_fury(40),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        ))
+    );
+}
+
+#[test]
+fn route_proceeds_are_swapped_into_fury_with_the_minimum_receive_as_bound() {
+    let mut deps = mock_dependencies(&[coin(1100, "uusd")]);
+    deps.querier
+        .with_pool(PAIR, fury_ust_pool(1000, 1000), Uint128::new(1000));
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        instantiate_msg(),
+    )
+    .unwrap();
+    let details = RouteSwapDetails {
+        receiver: USER.to_string(),
+        prev_balance: Uint128::new(100),
+        minimum_receive: Uint128::new(500),
+        pair_id: None,
+    };
+
+    let res =
+        swap_route_proceeds_to_fury(deps.as_mut(), mock_env(), details.clone(), USER.to_string())
+            .unwrap();
+    // 1000 received, 1.3% of platform and transaction fees
+    let offer_asset = Asset {
+        info: uusd(),
+        amount: Uint128::new(987),
+    };
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|sub_msg| sub_msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: PAIR.to_string(),
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset,
+                    belief_price: Some(Decimal::from_ratio(987u128, 500u128)),
+                    max_spread: Some(Decimal::zero()),
+                    to: Some(USER.to_string()),
+                })
+                .unwrap(),
+                funds: vec![coin(987, "uusd")],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee_collector".to_string(),
+                amount: vec![coin(13, "uusd")],
+            }),
+        ]
+    );
+
+    // The router returned less than the proxy held before
+    let err = swap_route_proceeds_to_fury(
+        deps.as_mut(),
+        mock_env(),
+        RouteSwapDetails {
+            prev_balance: Uint128::new(1200),
+            ..details
+        },
+        USER.to_string(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+}
+
+fn early_access_leaf(address: &str, cap: u128) -> Vec<u8> {
+    Sha256::digest(format!("{},{}", address, cap).as_bytes()).to_vec()
+}
+
+fn early_access_root(leaves: [Vec<u8>; 2]) -> String {
+    let [first, second] = leaves;
+    let pair = if first <= second {
+        [first, second].concat()
+    } else {
+        [second, first].concat()
+    };
+    hex::encode(Sha256::digest(&pair))
+}
+
+#[test]
+fn early_access_proof_is_checked_against_the_merkle_root() {
+    let user_leaf = early_access_leaf(USER, 1000);
+    let other_leaf = early_access_leaf("other", 2000);
+    let config = Config {
+        early_access_merkle_root: Some(early_access_root([user_leaf.clone(), other_leaf.clone()])),
+        ..proxy_config()
+    };
+    let user = Addr::unchecked(USER);
+
+    let proof = vec![hex::encode(&other_leaf)];
+    verify_early_access_proof(&config, &user, Uint128::new(1000), &proof).unwrap();
+    let proof = vec![hex::encode(&user_leaf)];
+    verify_early_access_proof(
+        &config,
+        &Addr::unchecked("other"),
+        Uint128::new(2000),
+        &proof,
+    )
+    .unwrap();
+
+    // The cap is part of the leaf
+    let proof = vec![hex::encode(&other_leaf)];
+    let err = verify_early_access_proof(&config, &user, Uint128::new(2000), &proof).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+
+    // The address and the cap are separated, "user1" with a cap of 1000 is not "user" with 11000
+    let config = Config {
+        early_access_merkle_root: Some(early_access_root([
+            early_access_leaf("user1", 1000),
+            other_leaf.clone(),
+        ])),
+        ..proxy_config()
+    };
+    let err = verify_early_access_proof(&config, &user, Uint128::new(11000), &proof).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+
+    let proof = vec!["not hex".to_string()];
+    let err = verify_early_access_proof(&config, &user, Uint128::new(1000), &proof).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+
+    let err = verify_early_access_proof(&config, &user, Uint128::new(1000), &[]).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+}
+
+#[test]
+fn early_access_proof_requires_a_valid_merkle_root() {
+    let user = Addr::unchecked(USER);
+    let proof = vec![hex::encode(early_access_leaf("other", 2000))];
+
+    let err =
+        verify_early_access_proof(&proxy_config(), &user, Uint128::new(1000), &proof).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleProof {});
+
+    let config = Config {
+        early_access_merkle_root: Some("not hex".to_string()),
+        ..proxy_config()
+    };
+    let err = verify_early_access_proof(&config, &user, Uint128::new(1000), &proof).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleRoot {});
+}
+
+fn pre_opening_msg(early_access_window_in_sec: Option<u64>) -> InstantiateMsg {
+    InstantiateMsg {
+        router_address: Some("router".to_string()),
+        swap_opening_date: Uint64::new(mock_env().block.time.plus_seconds(100).nanos()),
+        early_access_window_in_sec,
+        ..instantiate_msg()
+    }
+}
+
+#[test]
+fn routed_swaps_before_the_opening_require_early_access() {
+    let mut deps = instantiate_proxy(pre_opening_msg(None));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[coin(100, "uluna")]),
+        swap_luna_for_fury(40),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Swap is not enabled yet!!!"))
+    );
+
+    let mut deps = instantiate_proxy(pre_opening_msg(Some(1000)));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[coin(100, "uluna")]),
+        swap_luna_for_fury(40),
+    )
+    .unwrap();
+}
+
+#[test]
+fn routed_swaps_before_the_opening_use_the_early_access_allowance() {
+    let mut deps = mock_dependencies(&[coin(1100, "uusd")]);
+    deps.querier
+        .with_pool(PAIR, fury_ust_pool(1000, 1000), Uint128::new(1000));
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        pre_opening_msg(Some(1000)),
+    )
+    .unwrap();
+    let details = RouteSwapDetails {
+        receiver: USER.to_string(),
+        prev_balance: Uint128::new(100),
+        minimum_receive: Uint128::new(500),
+        pair_id: None,
+    };
+
+    let err =
+        swap_route_proceeds_to_fury(deps.as_mut(), mock_env(), details.clone(), USER.to_string())
+            .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotEligibleForEarlyAccess {
+            address: USER.to_string()
+        }
+    );
+
+    EARLY_ACCESS_ALLOWANCES
+        .save(
+            &mut deps.storage,
+            USER,
+            &EarlyAccessAllowance {
+                cap: Uint128::new(1500),
+                used: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    swap_route_proceeds_to_fury(deps.as_mut(), mock_env(), details.clone(), USER.to_string())
+        .unwrap();
+    // The quote asset returned by the router is counted, before fees
+    assert_eq!(
+        EARLY_ACCESS_ALLOWANCES
+            .load(&deps.storage, USER)
+            .unwrap()
+            .used,
+        Uint128::new(1000)
+    );
+
+    let err = swap_route_proceeds_to_fury(deps.as_mut(), mock_env(), details, USER.to_string())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::EarlyAccessCapExceeded {
+            required: Uint128::new(1000),
+            remaining: Uint128::new(500),
+        }
+    );
+}
+
+fn fury_offer(amount: u128) -> Asset {
+    Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked(FURY_TOKEN),
+        },
+        amount: Uint128::new(amount),
+    }
+}
+
+fn limited_config(swap_limits: SwapLimits) -> Config {
+    Config {
+        swap_limits,
+        ..proxy_config()
+    }
+}
+
+fn env_after(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(seconds);
+    env
+}
+
+#[test]
+fn swaps_without_limits_are_not_recorded() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let config = proxy_config();
+
+    apply_swap_limits(deps.as_mut(), &mock_env(), &config, USER, &fury_offer(1000)).unwrap();
+    assert!(!SWAP_ACTIVITY.has(&deps.storage, USER));
+}
+
+#[test]
+fn swap_offer_is_limited_to_a_share_of_the_reserve() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let config = limited_config(SwapLimits {
+        max_offer_reserve_ratio: Some(Decimal::percent(10)),
+        ..SwapLimits::default()
+    });
+
+    apply_swap_limits(deps.as_mut(), &mock_env(), &config, USER, &fury_offer(100)).unwrap();
+    let err =
+        apply_swap_limits(deps.as_mut(), &mock_env(), &config, USER, &fury_offer(101)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::OfferExceedsReserveLimit {
+            offer: Uint128::new(101),
+            max_offer: Uint128::new(100),
+        }
+    );
+}
+
+#[test]
+fn fury_swapped_over_the_window_is_capped() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let config = limited_config(SwapLimits {
+        max_fury_per_window: Some(Uint128::new(200)),
+        window_in_sec: 100,
+        ..SwapLimits::default()
+    });
+
+    // A quote asset offer is counted in the Fury it buys from the pool
+    let ust_offer = Asset {
+        info: uusd(),
+        amount: Uint128::new(100),
+    };
+    apply_swap_limits(deps.as_mut(), &mock_env(), &config, USER, &ust_offer).unwrap();
+    apply_swap_limits(
+        deps.as_mut(),
+        &env_after(50),
+        &config,
+        USER,
+        &fury_offer(109),
+    )
+    .unwrap();
+    assert_eq!(
+        SWAP_ACTIVITY.load(&deps.storage, USER).unwrap().swaps,
+        vec![
+            SwapRecord {
+                time: mock_env().block.time.seconds(),
+                fury_amount: Uint128::new(91),
+            },
+            SwapRecord {
+                time: env_after(50).block.time.seconds(),
+                fury_amount: Uint128::new(109),
+            },
+        ]
+    );
+
+    let err = apply_swap_limits(deps.as_mut(), &env_after(60), &config, USER, &fury_offer(1))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SwapWindowLimitExceeded {
+            fury_amount: Uint128::new(1),
+            remaining: Uint128::zero(),
+        }
+    );
+
+    // Other addresses have their own window
+    apply_swap_limits(
+        deps.as_mut(),
+        &env_after(60),
+        &config,
+        "other",
+        &fury_offer(200),
+    )
+    .unwrap();
+
+    // The first swap leaves the window
+    apply_swap_limits(
+        deps.as_mut(),
+        &env_after(100),
+        &config,
+        USER,
+        &fury_offer(91),
+    )
+    .unwrap();
+    assert_eq!(
+        SWAP_ACTIVITY.load(&deps.storage, USER).unwrap().swaps.len(),
+        2
+    );
+}
+
+#[test]
+fn swaps_of_an_address_are_spaced_by_the_cooldown() {
+    let mut deps = instantiate_proxy(instantiate_msg());
+    let config = limited_config(SwapLimits {
+        cooldown_in_sec: 60,
+        ..SwapLimits::default()
+    });
+
+    apply_swap_limits(deps.as_mut(), &mock_env(), &config, USER, &fury_offer(10)).unwrap();
+    let err = apply_swap_limits(
+        deps.as_mut(),
+        &env_after(59),
+        &config,
+        USER,
+        &fury_offer(10),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SwapCooldown {
+            next_swap: env_after(60).block.time.seconds()
+        }
+    );
+    apply_swap_limits(
+        deps.as_mut(),
+        &env_after(60),
+        &config,
+        USER,
+        &fury_offer(10),
+    )
+    .unwrap();
+}
+
+const GENERATOR: &str = "generator";
+
+fn generator_proxy() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = instantiate_proxy(InstantiateMsg {
+        generator_address: Some(GENERATOR.to_string()),
+        ..instantiate_msg()
+    });
+    deps.querier.with_generator(
+        GENERATOR,
+        PendingTokenResponse {
+            pending: Uint128::new(10),
+            pending_on_proxy: Some(Uint128::new(5)),
+        },
+        RewardInfoResponse {
+            base_reward_token: Addr::unchecked("astro"),
+            proxy_reward_token: Some(Addr::unchecked("proxy_reward")),
+        },
+    );
+    deps.querier
+        .with_token_balances(&[("proxy_reward", &[(MOCK_CONTRACT_ADDR, Uint128::new(100))])]);
+    PROTOCOL_LIQUIDITY
+        .save(&mut deps.storage, LP_TOKEN, &Uint128::new(1000))
+        .unwrap();
+    deps
+}
+
+fn holder_transfer(token: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: "lp_holder".to_string(),
+            amount: Uint128::new(amount),
+        })
+        .unwrap(),
+        funds: vec![],
+    })
+}
+
+fn generator_reply(deps: DepsMut, id: u64) -> Response {
+    reply(
+        deps,
+        mock_env(),
+        Reply {
+            id,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn harvest_claims_all_generator_rewards_for_the_lp_tokens_holder() {
+    let mut deps = generator_proxy();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(USER, &[]),
+        ExecuteMsg::HarvestProtocolLiquidity {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::HarvestProtocolLiquidity {},
+    )
+    .unwrap();
+    let sub_req_id = SUB_REQ_ID.load(&deps.storage).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: GENERATOR.to_string(),
+                    msg: to_binary(&GeneratorExecuteMsg::Withdraw {
+                        lp_token: Addr::unchecked(LP_TOKEN),
+                        amount: Uint128::zero(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                },
+                sub_req_id,
+            ),
+            SubMsg::new(holder_transfer("astro", 10)),
+        ]
+    );
+    let details = SUB_MESSAGE_DETAILS
+        .load(&deps.storage, sub_req_id.to_string())
+        .unwrap();
+    assert_eq!(details.request_type, SubMessageType::GeneratorSubMsg);
+    assert_eq!(
+        from_binary::<ProxyRewardDetails>(&details.sub_message_payload).unwrap(),
+        ProxyRewardDetails {
+            recipient: "lp_holder".to_string(),
+            reward_token: "proxy_reward".to_string(),
+            prev_balance: Uint128::new(100),
+        }
+    );
+
+    // The pool paid its proxy rewards to the proxy during the claim
+    deps.querier
+        .with_token_balances(&[("proxy_reward", &[(MOCK_CONTRACT_ADDR, Uint128::new(150))])]);
+    let res = generator_reply(deps.as_mut(), sub_req_id);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(holder_transfer("proxy_reward", 50))]
+    );
+    assert!(!SUB_MESSAGE_DETAILS.has(&deps.storage, sub_req_id.to_string()));
+}
+
+#[test]
+fn generator_calls_without_proxy_rewards_forward_nothing() {
+    let mut deps = generator_proxy();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::UnstakeProtocolLiquidity {
+            lp_token: LP_TOKEN.to_string(),
+            amount: Uint128::new(400),
+        },
+    )
+    .unwrap();
+    let sub_req_id = SUB_REQ_ID.load(&deps.storage).unwrap();
+    assert_eq!(res.messages[0].id, sub_req_id);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Success);
+    assert_eq!(res.messages[1].msg, holder_transfer(LP_TOKEN, 400),);
+    assert_eq!(
+        PROTOCOL_LIQUIDITY.load(&deps.storage, LP_TOKEN).unwrap(),
+        Uint128::new(600)
+    );
+
+    let res = generator_reply(deps.as_mut(), sub_req_id);
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.contains(&attr("proxy_reward", "0")));
+}
+
+#[test]
+fn generator_calls_of_pools_without_proxy_rewards_need_no_reply() {
+    let mut deps = generator_proxy();
+    deps.querier.with_generator(
+        GENERATOR,
+        PendingTokenResponse {
+            pending: Uint128::new(10),
+            pending_on_proxy: None,
+        },
+        RewardInfoResponse {
+            base_reward_token: Addr::unchecked("astro"),
+            proxy_reward_token: None,
+        },
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::HarvestProtocolLiquidity {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: GENERATOR.to_string(),
+                msg: to_binary(&GeneratorExecuteMsg::Withdraw {
+                    lp_token: Addr::unchecked(LP_TOKEN),
+                    amount: Uint128::zero(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(holder_transfer("astro", 10)),
+        ]
+    );
+    assert!(SUB_REQ_ID.may_load(&deps.storage).unwrap().is_none());
+}
+
+#[test]
+fn unstake_is_limited_to_the_staked_lp_tokens() {
+    let mut deps = generator_proxy();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        ExecuteMsg::UnstakeProtocolLiquidity {
+            lp_token: LP_TOKEN.to_string(),
+            amount: Uint128::new(1001),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientProtocolLiquidity {
+            staked: Uint128::new(1000)
+        }
+    );
+}
+
+#[test]
+fn withdrawn_assets_are_read_from_the_pair_refund() {
+    let config = proxy_config();
+    let events = vec![
+        pair_event("fake_token", &[("refund_assets", "999999uusd")]),
+        pair_event(PAIR, &[("refund_assets", "100fury_token, 200uusd")]),
+    ];
+
+    assert_eq!(
+        withdrawn_assets(&config, &events).unwrap(),
+        vec![
+            Asset {
+                info: fury_asset_info(&config),
+                amount: Uint128::new(100),
+            },
+            Asset {
+                info: uusd(),
+                amount: Uint128::new(200),
+            },
+        ]
+    );
+}
+
+#[test]
+fn malformed_refunds_are_rejected() {
+    let config = proxy_config();
+    let invalid = StdError::generic_err("Invalid refund_assets in reply");
+
+    for refund_assets in [
+        "100uluna, 200uusd",
+        "fury_token, 200uusd",
+        "100fury_token 200uusd",
+    ] {
+        let events = vec![pair_event(PAIR, &[("refund_assets", refund_assets)])];
+        assert_eq!(withdrawn_assets(&config, &events).unwrap_err(), invalid);
+    }
+
+    let events = vec![pair_event("fake_token", &[("refund_assets", "100uusd")])];
+    assert!(withdrawn_assets(&config, &events).is_err());
+}
+
+fn legacy_proxy() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = mock_dependencies(&[]);
+    set_contract_version(&mut deps.storage, CONTRACT_NAME, "1.0.0").unwrap();
+    CONFIGV100
+        .save(
+            &mut deps.storage,
+            &ConfigV100 {
+                admin_address: Addr::unchecked(ADMIN),
+                custom_token_address: Addr::unchecked(FURY_TOKEN),
+                pair_discount_rate: 1000,
+                pair_bonding_period_in_sec: 100,
+                pair_fury_reward_wallet: Addr::unchecked(PAIR_REWARD_WALLET),
+                pair_lp_tokens_holder: Addr::unchecked("lp_holder"),
+                native_discount_rate: 500,
+                native_bonding_period_in_sec: 200,
+                native_investment_reward_wallet: Addr::unchecked("native_reward_wallet"),
+                native_investment_receive_wallet: Addr::unchecked("native_receive_wallet"),
+                authorized_liquidity_provider: Addr::unchecked("liquidity_provider"),
+                swap_opening_date: Timestamp::from_seconds(1_000),
+                pool_pair_address: PAIR.to_string(),
+                liquidity_token: Addr::unchecked(LP_TOKEN),
+                platform_fees_collector_wallet: Addr::unchecked("fee_collector"),
+                platform_fees: Uint128::new(100),
+                transaction_fees: Uint128::new(30),
+                swap_fees: Uint128::zero(),
+                max_bonding_limit_per_user: 10,
+            },
+        )
+        .unwrap();
+    for (user, bonding_period, start) in [("alice", 100, 0), ("bob", 200, SECONDS_PER_DAY)] {
+        BONDED_REWARDS_DETAILSV100
+            .save(
+                &mut deps.storage,
+                user.to_string(),
+                &vec![
+                    BondedRewardsDetailsV100 {
+                        user_address: user.to_string(),
+                        bonded_amount: Uint128::new(500),
+                        bonding_period,
+                        bonding_start_timestamp: Timestamp::from_seconds(start),
+                    },
+                    BondedRewardsDetailsV100 {
+                        user_address: user.to_string(),
+                        bonded_amount: Uint128::zero(),
+                        bonding_period,
+                        bonding_start_timestamp: Timestamp::from_seconds(start),
+                    },
+                ],
+            )
+            .unwrap();
+    }
+    deps
+}
+
+fn legacy_migrate_msg(limit: Option<u32>) -> MigrateMsg {
+    MigrateMsg {
+        legacy_bond_types: vec![
+            LegacyBondType {
+                bonding_period: 100,
+                bond_type: BondType::Pair,
+            },
+            LegacyBondType {
+                bonding_period: 200,
+                bond_type: BondType::Native,
+            },
+        ],
+        limit,
+    }
+}
+
+#[test]
+fn migrate_from_v100_converts_the_config_and_bonds() {
+    let mut deps = legacy_proxy();
+
+    let res = migrate(deps.as_mut(), mock_env(), legacy_migrate_msg(None)).unwrap();
+    assert!(res.attributes.contains(&attr("legacy_bonds_left", "false")));
+    assert_eq!(
+        get_contract_version(&deps.storage).unwrap().version,
+        CONTRACT_VERSION
+    );
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.admin_address, Addr::unchecked(ADMIN));
+    assert_eq!(config.swap_opening_date, Timestamp::from_seconds(1_000));
+    assert_eq!(config.liquidity_token, Addr::unchecked(LP_TOKEN));
+    assert_eq!(config.pair_vesting_schedule, VestingSchedule::default());
+    assert_eq!(config.fee_collectors, vec![]);
+    assert_eq!(config.generator_address, None);
+
+    // Emptied bonds are dropped, the others get new ids keyed by their owner
+    let alice_bond = BONDED_REWARDS_DETAILS
+        .load(&deps.storage, ("alice", U64Key::new(1)))
+        .unwrap();
+    assert_eq!(alice_bond.bond_type, BondType::Pair);
+    assert_eq!(alice_bond.bonded_amount, Uint128::new(500));
+    assert_eq!(alice_bond.vesting_schedule, VestingSchedule::Cliff);
+    let bob_bond = BONDED_REWARDS_DETAILS
+        .load(&deps.storage, ("bob", U64Key::new(2)))
+        .unwrap();
+    assert_eq!(bob_bond.bond_type, BondType::Native);
+    assert_eq!(bob_bond.bonding_period, 200);
+    assert_eq!(BOND_ID.load(&deps.storage).unwrap(), 2);
+    assert_eq!(
+        BOND_OWNERS.load(&deps.storage, U64Key::new(1)).unwrap(),
+        "alice"
+    );
+    assert_eq!(
+        BOND_OWNERS.load(&deps.storage, U64Key::new(2)).unwrap(),
+        "bob"
+    );
+
+    for bond_type in [BondType::Pair, BondType::Native] {
+        assert_eq!(
+            BOND_STATS
+                .load(&deps.storage, bond_type.to_string())
+                .unwrap(),
+            BondStats {
+                outstanding_bonds: 1,
+                outstanding_fury: Uint128::new(500),
+                ust_raised: Uint128::zero(),
+            }
+        );
+    }
+    assert_eq!(
+        PRE_OPENING_MATURITIES
+            .load(&deps.storage, U64Key::new(100))
+            .unwrap(),
+        MaturityBucket {
+            pair: Uint128::new(500),
+            native: Uint128::zero(),
+        }
+    );
+    assert_eq!(
+        MATURITY_HISTOGRAM
+            .load(&deps.storage, U64Key::new(1))
+            .unwrap(),
+        MaturityBucket {
+            pair: Uint128::zero(),
+            native: Uint128::new(500),
+        }
+    );
+
+    assert!(BONDED_REWARDS_DETAILSV100
+        .keys(&deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none());
+}
+
+#[test]
+fn migrate_from_v100_moves_bonds_in_batches() {
+    let mut deps = legacy_proxy();
+
+    let res = migrate(deps.as_mut(), mock_env(), legacy_migrate_msg(Some(1))).unwrap();
+    assert!(res.attributes.contains(&attr("legacy_bonds_left", "true")));
+    assert!(BOND_OWNERS.has(&deps.storage, U64Key::new(1)));
+    assert!(!BOND_OWNERS.has(&deps.storage, U64Key::new(2)));
+    assert!(BONDED_REWARDS_DETAILSV100.has(&deps.storage, "bob".to_string()));
+
+    // Migrating again to the same version moves the next batch
+    let res = migrate(deps.as_mut(), mock_env(), legacy_migrate_msg(Some(1))).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("previous_contract_version", CONTRACT_VERSION)));
+    assert!(res.attributes.contains(&attr("legacy_bonds_left", "false")));
+    assert_eq!(
+        BOND_OWNERS.load(&deps.storage, U64Key::new(2)).unwrap(),
+        "bob"
+    );
+    assert!(!BONDED_REWARDS_DETAILSV100.has(&deps.storage, "bob".to_string()));
+}
+
+#[test]
+fn migrate_from_v100_requires_the_type_of_every_bonding_period() {
+    let mut deps = legacy_proxy();
+
+    let mut msg = legacy_migrate_msg(None);
+    msg.legacy_bond_types.pop();
+    let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownLegacyBondPeriod {
+            bonding_period: 200
+        }
+    );
+}
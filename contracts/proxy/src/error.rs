@@ -5,11 +5,205 @@ use thiserror::Error;
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
-    
+
     #[error("Unauthorized")]
     Unauthorized {},
-    
-    #[error("Fees received = {received}uusd whereas required = {required}uusd")]
+
+    #[error("Discount rate must be below 100%")]
+    InvalidDiscountRate {},
+
+    #[error("Sum of platform, transaction and swap fees must be below 100%")]
+    InvalidFees {},
+
+    #[error("Vesting cliff must be shorter than the bonding period")]
+    InvalidVestingSchedule {},
+
+    #[error("Epoch length must be set when limiting Fury bonded per epoch")]
+    InvalidBondCapacity {},
+
+    #[error("Bond of {required} Fury exceeds the remaining {remaining} Fury of this epoch")]
+    EpochBondCapacityExceeded {
+        required: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error(
+        "Bond of {required} Fury exceeds the remaining {remaining} Fury of the programme budget"
+    )]
+    BondBudgetExceeded {
+        required: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Reward wallet can only provide {available} Fury whereas {required} Fury is required")]
+    InsufficientRewardWalletFunds {
+        required: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Spot price deviates too much from TWAP: {spot_amount} Fury at spot against {twap_amount} Fury at TWAP")]
+    TwapDeviationTooHigh {
+        spot_amount: Uint128,
+        twap_amount: Uint128,
+    },
+
+    #[error("Bond term {term_id} not found")]
+    BondTermNotFound { term_id: u64 },
+
+    #[error("Bond term {term_id} is retired or not offered for this bond type")]
+    BondTermUnavailable { term_id: u64 },
+
+    #[error("Early unbond penalty rate must not exceed 100%")]
+    InvalidPenaltyRate {},
+
+    #[error("Early unbonds are disabled")]
+    EarlyUnbondDisabled {},
+
+    #[error("Fury fee discount must not exceed 100%")]
+    InvalidFeeDiscount {},
+
+    #[error("Fee collector shares must add up to 100% and the maker address must be set to give it a share")]
+    InvalidFeeCollectors {},
+
+    #[error("Fees of {fury_fees} Fury exceed the amount of {amount} Fury")]
+    FuryFeesExceedAmount { fury_fees: Uint128, amount: Uint128 },
+
+    #[error("Platform fees {fees} exceed the swap proceeds {proceeds}")]
+    FeesExceedProceeds { fees: Uint128, proceeds: Uint128 },
+
+    #[error("Operation exceeds max spread limit")]
+    MaxSpreadAssertion {},
+
+    #[error("Sent {received} Fury whereas the assets provide {required} Fury")]
+    FuryAmountMismatch {
+        required: Uint128,
+        received: Uint128,
+    },
+
+    #[error("Liquidity of a pair quoted in a native asset cannot be provided through a CW20 send")]
+    NativeQuoteAssetNotSupported {},
+
+    #[error("A minimum receive must be set for swaps through the router")]
+    MinimumReceiveRequired {},
+
+    #[error("Only the offered native asset can be attached")]
+    UnexpectedFunds {},
+
+    #[error("Router address is not configured")]
+    RouterNotConfigured {},
+
+    #[error("Route must end with a swap of the quote asset into Fury and not touch Fury before")]
+    InvalidSwapRoute {},
+
+    #[error("Pair must be quoted in an asset other than Fury")]
+    InvalidPairSettings {},
+
+    #[error("Pair {pair_id} not found")]
+    PairNotFound { pair_id: String },
+
+    #[error("Pair {pair_id} still has pending requests")]
+    PairInUse { pair_id: String },
+
+    #[error("Referral fee share must not exceed 100%")]
+    InvalidReferralFeeShare {},
+
+    #[error("Cannot refer yourself")]
+    InvalidReferrer {},
+
+    #[error("No referral rewards to claim")]
+    NoReferralRewards {},
+
+    #[error("Early access opens at {window_start}")]
+    EarlyAccessNotOpen { window_start: u64 },
+
+    #[error("{address} has no early access")]
+    NotEligibleForEarlyAccess { address: String },
+
+    #[error("Early access of {required} exceeds the remaining allowance of {remaining}")]
+    EarlyAccessCapExceeded {
+        required: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Invalid early access Merkle root")]
+    InvalidMerkleRoot {},
+
+    #[error("Invalid early access Merkle proof")]
+    InvalidMerkleProof {},
+
+    #[error(
+        "Swap limits need a window when capping Fury per window and a reserve ratio of at most 1"
+    )]
+    InvalidSwapLimits {},
+
+    #[error(
+        "Offer of {offer} exceeds the maximum of {max_offer} allowed against the pool reserves"
+    )]
+    OfferExceedsReserveLimit { offer: Uint128, max_offer: Uint128 },
+
+    #[error(
+        "Swap of {fury_amount} Fury exceeds the remaining {remaining} Fury of the rolling window"
+    )]
+    SwapWindowLimitExceeded {
+        fury_amount: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Swap cooldown active until {next_swap}")]
+    SwapCooldown { next_swap: u64 },
+
+    #[error("Swaps are paused")]
+    SwapsPaused {},
+
+    #[error("Pair bonding is paused")]
+    PairBondingPaused {},
+
+    #[error("Native bonding is paused")]
+    NativeBondingPaused {},
+
+    #[error("Claims are paused")]
+    ClaimsPaused {},
+
+    #[error("Liquidity withdrawals are paused")]
+    WithdrawalsPaused {},
+
+    #[error("Withdrawn {received} is below the minimum of {minimum}")]
+    WithdrawnAssetBelowMinimum { minimum: String, received: String },
+
+    #[error("No generator is configured")]
+    GeneratorNotSet {},
+
+    #[error("The generator cannot be changed while protocol liquidity is staked in it")]
+    ProtocolLiquidityStaked {},
+
+    #[error("Only {staked} LP tokens are staked")]
+    InsufficientProtocolLiquidity { staked: Uint128 },
+
+    #[error("An error occurred during migration")]
+    MigrationError {},
+
+    #[error("No bond type given for the version 1.0.0 bonds of {bonding_period} seconds")]
+    UnknownLegacyBondPeriod { bonding_period: u64 },
+
+    #[error("Bond transfers are disabled")]
+    BondTransfersDisabled {},
+
+    #[error("Invalid token id {token_id}")]
+    InvalidTokenId { token_id: String },
+
+    #[error("Approval has already expired")]
+    ApprovalExpired {},
+
+    #[error("Bond limit reached for {user_address}")]
+    BondLimitReached { user_address: String },
+
+    #[error("Pending request {request_id} not found")]
+    PendingRequestNotFound { request_id: u64 },
+
+    #[error("Bond {bond_id} not found")]
+    BondNotFound { bond_id: u64 },
+
+    #[error("Fees received = {received} whereas required = {required}")]
     InsufficientFees {
         received: Uint128,
         required: Uint128,
@@ -1,5 +1,9 @@
 pub mod contract;
 mod error;
+mod migration;
 pub mod msg;
 mod query;
 mod state;
+
+#[cfg(test)]
+mod mock_querier;
@@ -0,0 +1,44 @@
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The structure describes the proxy config for version 1.0.0.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigV100 {
+    pub admin_address: Addr,
+    pub custom_token_address: Addr,
+    pub pair_discount_rate: u16,
+    pub pair_bonding_period_in_sec: u64,
+    pub pair_fury_reward_wallet: Addr,
+    pub pair_lp_tokens_holder: Addr,
+    pub native_discount_rate: u16,
+    pub native_bonding_period_in_sec: u64,
+    pub native_investment_reward_wallet: Addr,
+    pub native_investment_receive_wallet: Addr,
+    pub authorized_liquidity_provider: Addr,
+    pub swap_opening_date: Timestamp,
+    pub pool_pair_address: String,
+    pub liquidity_token: Addr,
+    pub platform_fees_collector_wallet: Addr,
+    pub platform_fees: Uint128,
+    pub transaction_fees: Uint128,
+    pub swap_fees: Uint128,
+    pub max_bonding_limit_per_user: u64,
+}
+
+pub const CONFIGV100: Item<ConfigV100> = Item::new("\u{0}\u{6}config");
+
+/// The structure describes a bond for version 1.0.0, which had neither an id nor a type.
+/// The bonded amount is what is left to claim.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondedRewardsDetailsV100 {
+    pub user_address: String,
+    pub bonded_amount: Uint128,
+    pub bonding_period: u64,
+    pub bonding_start_timestamp: Timestamp,
+}
+
+/// List of bonds per user for version 1.0.0, the key is the user address
+pub const BONDED_REWARDS_DETAILSV100: Map<String, Vec<BondedRewardsDetailsV100>> =
+    Map::new("bonded_rewards_details");
@@ -0,0 +1,278 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Binary, Coin, Decimal, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use std::collections::HashMap;
+
+use astroport::asset::Asset;
+use astroport::generator::{
+    PendingTokenResponse, QueryMsg as GeneratorQueryMsg, RewardInfoResponse,
+};
+use astroport::oracle::QueryMsg as OracleQueryMsg;
+use astroport::pair::{PoolResponse, QueryMsg as PairQueryMsg};
+use cw20::{AllowanceResponse, BalanceResponse, Cw20QueryMsg, Expiration};
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<TerraQueryWrapper>,
+    token_querier: TokenQuerier,
+    tax_querier: TaxQuerier,
+    pair_querier: PairQuerier,
+    generator_querier: GeneratorQuerier,
+    oracle_querier: OracleQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // token contract -> holder -> balance
+    balances: HashMap<String, HashMap<String, Uint128>>,
+    // token contract -> (owner, spender) -> allowance
+    allowances: HashMap<String, HashMap<(String, String), Uint128>>,
+}
+
+#[derive(Clone, Default)]
+pub struct TaxQuerier {
+    rate: Decimal,
+    caps: HashMap<String, Uint128>,
+}
+
+#[derive(Clone, Default)]
+pub struct PairQuerier {
+    // pair contract -> pool
+    pools: HashMap<String, PoolResponse>,
+}
+
+#[derive(Clone, Default)]
+pub struct GeneratorQuerier {
+    // generator contract -> pending rewards of every staker and reward tokens of every pool
+    generators: HashMap<String, (PendingTokenResponse, RewardInfoResponse)>,
+}
+
+#[derive(Clone, Default)]
+pub struct OracleQuerier {
+    // oracle contract -> average price of the consulted token
+    prices: HashMap<String, Decimal>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+        match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if route == &TerraRoute::Treasury {
+                    match query_data {
+                        TerraQuery::TaxRate {} => {
+                            let res = TaxRateResponse {
+                                rate: self.tax_querier.rate,
+                            };
+                            SystemResult::Ok(to_binary(&res).into())
+                        }
+                        TerraQuery::TaxCap { denom } => {
+                            let cap = self
+                                .tax_querier
+                                .caps
+                                .get(denom)
+                                .copied()
+                                .unwrap_or_default();
+                            let res = TaxCapResponse { cap };
+                            SystemResult::Ok(to_binary(&res).into())
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                if let Some(pool) = self.pair_querier.pools.get(contract_addr) {
+                    self.handle_pair_query(pool, msg)
+                } else if let Some(generator) = self.generator_querier.generators.get(contract_addr)
+                {
+                    self.handle_generator_query(generator, msg)
+                } else if let Some(price) = self.oracle_querier.prices.get(contract_addr) {
+                    self.handle_oracle_query(*price, msg)
+                } else {
+                    self.handle_token_query(contract_addr, msg)
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+
+    fn handle_pair_query(&self, pool: &PoolResponse, msg: &Binary) -> QuerierResult {
+        match from_binary(msg).unwrap() {
+            PairQueryMsg::Pool {} => SystemResult::Ok(to_binary(pool).into()),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    fn handle_oracle_query(&self, price: Decimal, msg: &Binary) -> QuerierResult {
+        match from_binary(msg).unwrap() {
+            // the oracle answers with a cosmwasm_bignumber::Uint256, serialized as a decimal string
+            OracleQueryMsg::Consult { amount, .. } => {
+                SystemResult::Ok(to_binary(&(amount * price).to_string()).into())
+            }
+        }
+    }
+
+    fn handle_generator_query(
+        &self,
+        generator: &(PendingTokenResponse, RewardInfoResponse),
+        msg: &Binary,
+    ) -> QuerierResult {
+        let (pending, reward_info) = generator;
+        match from_binary(msg).unwrap() {
+            GeneratorQueryMsg::PendingToken { .. } => SystemResult::Ok(to_binary(pending).into()),
+            GeneratorQueryMsg::RewardInfo { .. } => SystemResult::Ok(to_binary(reward_info).into()),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    fn handle_token_query(&self, contract_addr: &str, msg: &Binary) -> QuerierResult {
+        match from_binary(msg).unwrap() {
+            Cw20QueryMsg::Balance { address } => {
+                let balance = self
+                    .token_querier
+                    .balances
+                    .get(contract_addr)
+                    .and_then(|balances| balances.get(&address))
+                    .copied()
+                    .unwrap_or_default();
+                SystemResult::Ok(to_binary(&BalanceResponse { balance }).into())
+            }
+            Cw20QueryMsg::Allowance { owner, spender } => {
+                let allowance = self
+                    .token_querier
+                    .allowances
+                    .get(contract_addr)
+                    .and_then(|allowances| allowances.get(&(owner, spender)))
+                    .copied()
+                    .unwrap_or_default();
+                SystemResult::Ok(
+                    to_binary(&AllowanceResponse {
+                        allowance,
+                        expires: Expiration::Never {},
+                    })
+                    .into(),
+                )
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
+        WasmMockQuerier {
+            base,
+            token_querier: TokenQuerier::default(),
+            tax_querier: TaxQuerier::default(),
+            pair_querier: PairQuerier::default(),
+            generator_querier: GeneratorQuerier::default(),
+            oracle_querier: OracleQuerier::default(),
+        }
+    }
+
+    // configure the native balance of an address
+    pub fn with_balance(&mut self, addr: &str, balance: Vec<Coin>) {
+        self.base.update_balance(addr, balance);
+    }
+
+    // configure the token balances mock querier
+    pub fn with_token_balances(&mut self, balances: &[(&str, &[(&str, Uint128)])]) {
+        for (contract_addr, balances) in balances.iter() {
+            let contract_balances = self
+                .token_querier
+                .balances
+                .entry(contract_addr.to_string())
+                .or_default();
+            for (addr, balance) in balances.iter() {
+                contract_balances.insert(addr.to_string(), *balance);
+            }
+        }
+    }
+
+    // configure the token allowances mock querier
+    pub fn with_token_allowance(
+        &mut self,
+        contract_addr: &str,
+        owner: &str,
+        spender: &str,
+        allowance: Uint128,
+    ) {
+        self.token_querier
+            .allowances
+            .entry(contract_addr.to_string())
+            .or_default()
+            .insert((owner.to_string(), spender.to_string()), allowance);
+    }
+
+    // configure the tax mock querier
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&str, Uint128)]) {
+        self.tax_querier.rate = rate;
+        for (denom, cap) in caps.iter() {
+            self.tax_querier.caps.insert(denom.to_string(), *cap);
+        }
+    }
+
+    // configure the pool reserves of a pair
+    pub fn with_pool(&mut self, pair_addr: &str, assets: [Asset; 2], total_share: Uint128) {
+        self.pair_querier.pools.insert(
+            pair_addr.to_string(),
+            PoolResponse {
+                assets,
+                total_share,
+            },
+        );
+    }
+
+    // configure the pending rewards and reward tokens of a generator
+    pub fn with_generator(
+        &mut self,
+        generator_addr: &str,
+        pending: PendingTokenResponse,
+        reward_info: RewardInfoResponse,
+    ) {
+        self.generator_querier
+            .generators
+            .insert(generator_addr.to_string(), (pending, reward_info));
+    }
+
+    // configure the average price returned by an oracle
+    pub fn with_oracle(&mut self, oracle_addr: &str, price: Decimal) {
+        self.oracle_querier
+            .prices
+            .insert(oracle_addr.to_string(), price);
+    }
+}
@@ -1,8 +1,12 @@
-use astroport::asset::Asset;
-use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128, Uint64};
-use cw20::Cw20ReceiveMsg;
+use astroport::asset::{Asset, AssetInfo};
+use astroport::router::SwapOperation;
+use cosmwasm_std::{
+    to_binary, Binary, CosmosMsg, Decimal, StdResult, Timestamp, Uint128, Uint64, WasmMsg,
+};
+use cw20::{Cw20ReceiveMsg, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMsg {
@@ -46,6 +50,201 @@ pub struct InstantiateMsg {
     pub swap_fees: Uint128,
     /// Maximum number of simultaneous outstanding Bonds of discounted Reward Fury Tokens permitted per user
     pub max_bonding_limit_per_user: u64,
+    /// Vesting curve for pair bonds, defaults to a cliff at the end of the bonding period
+    pub pair_vesting_schedule: Option<VestingSchedule>,
+    /// Vesting curve for native bonds, defaults to a cliff at the end of the bonding period
+    pub native_vesting_schedule: Option<VestingSchedule>,
+    /// Astroport oracle contract of the pool, bonds are priced from its TWAP when set
+    pub oracle_address: Option<String>,
+    /// Maximum relative deviation of the spot price from the TWAP for a bond to be accepted
+    pub max_twap_deviation: Option<Decimal>,
+    /// Issuance limits for pair bonds, unlimited by default
+    pub pair_bond_capacity: Option<BondCapacityConfig>,
+    /// Issuance limits for native bonds, unlimited by default
+    pub native_bond_capacity: Option<BondCapacityConfig>,
+    /// Lets holders transfer their bonds as CW721 tokens, disabled by default
+    pub bonds_transferable: Option<bool>,
+    /// Maximum penalty of an early unbond, 100% = 10000, early unbonds are disabled when not set
+    pub early_unbond_penalty_rate: Option<u16>,
+    /// Where the Fury forfeited by early unbonds is sent, the reward wallet by default
+    pub early_unbond_penalty_destination: Option<PenaltyDestination>,
+    /// Asset the Fury pool is quoted in and platform fees are paid in, uusd by default
+    pub quote_asset: Option<AssetInfo>,
+    /// Discount on platform fees paid in Fury, 100% = 10000
+    pub fury_fee_discount: Option<u16>,
+    /// Weighted recipients of the platform fees, all fees go to the collector wallet when empty
+    pub fee_collectors: Option<Vec<FeeCollector>>,
+    /// Astroport maker contract that can be given a share of the platform fees
+    pub maker_address: Option<String>,
+    /// Astroport router used to buy Fury with other assets
+    pub router_address: Option<String>,
+    /// Address allowed to pause the proxy actions in an emergency, only the admin when not set
+    pub guardian_address: Option<String>,
+    /// Share of the platform fees credited to the referrer of a swap or bond, 100% = 10000
+    pub referral_fee_share: Option<u16>,
+    /// Seconds before the swap opening date during which early access addresses can swap,
+    /// early access is disabled by default
+    pub early_access_window_in_sec: Option<u64>,
+    /// Hex encoded Merkle root of the early access addresses and their caps, each leaf is the
+    /// SHA-256 of `{address},{cap}` with the cap in the smallest unit of the quote asset
+    pub early_access_merkle_root: Option<String>,
+    /// Anti-whale limits on swaps, unlimited by default
+    pub swap_limits: Option<SwapLimits>,
+    /// Astroport generator the proxy stakes the LP tokens of pair bonds in, the LP tokens are
+    /// delivered to pair_lp_tokens_holder when not set. The stake is held by the proxy as the
+    /// beneficiary of the deposits, not by pair_lp_tokens_holder
+    pub generator_address: Option<String>,
+}
+
+/// This structure describes a migration message.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct MigrateMsg {
+    /// Bond type of the version 1.0.0 bonds per bonding period, as these bonds did not record
+    /// their type. Bonds of an unlisted bonding period fail the migration
+    #[serde(default)]
+    pub legacy_bond_types: Vec<LegacyBondType>,
+    /// Maximum number of version 1.0.0 users whose bonds are moved by this migration, all when
+    /// not set. The bonds left are moved by migrating again to the same version
+    pub limit: Option<u32>,
+}
+
+/// Bond type given to the version 1.0.0 bonds of a bonding period
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct LegacyBondType {
+    pub bonding_period: u64,
+    pub bond_type: BondType,
+}
+
+/// Kind of investment a bond was issued for
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BondType {
+    /// Fury and UST were both provided
+    Pair,
+    /// Only UST was provided
+    Native,
+}
+
+impl Default for BondType {
+    fn default() -> Self {
+        BondType::Pair
+    }
+}
+
+impl fmt::Display for BondType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BondType::Pair => write!(f, "pair"),
+            BondType::Native => write!(f, "native"),
+        }
+    }
+}
+
+/// Limits on the discounted Fury issued for a bond type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BondCapacityConfig {
+    /// Length of an issuance epoch in seconds
+    pub epoch_length_in_sec: u64,
+    /// Maximum Fury bonded per epoch
+    pub max_fury_per_epoch: Option<Uint128>,
+    /// Maximum Fury bonded over the whole programme
+    pub total_budget: Option<Uint128>,
+}
+
+/// Anti-whale limits on the swaps made through the proxy
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct SwapLimits {
+    /// Maximum offer of a swap as a fraction of the pool reserve of the offered asset
+    pub max_offer_reserve_ratio: Option<Decimal>,
+    /// Maximum Fury bought or sold by an address over the rolling window
+    pub max_fury_per_window: Option<Uint128>,
+    /// Length of the rolling window in seconds
+    pub window_in_sec: u64,
+    /// Seconds an address has to wait between two swaps
+    pub cooldown_in_sec: u64,
+}
+
+/// Where the Fury forfeited by early unbonds is sent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PenaltyDestination {
+    /// Back to the reward wallet the bond was funded from
+    RewardWallet,
+    /// Burnt from the Fury supply
+    Burn,
+    /// To the given address
+    Address { address: String },
+}
+
+impl Default for PenaltyDestination {
+    fn default() -> Self {
+        PenaltyDestination::RewardWallet
+    }
+}
+
+/// Where a share of the platform fees is sent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRecipient {
+    /// To the given address
+    Address { address: String },
+    /// To the Astroport maker, which converts it and distributes it to xASTRO stakers
+    Maker,
+}
+
+/// Share of the platform fees sent to a recipient
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeCollector {
+    pub recipient: FeeRecipient,
+    /// 100% = 10000, the shares of all collectors add up to 100%
+    pub share: u16,
+}
+
+/// Settings of a Fury pool managed next to the configured one. Reward wallets, LP token holder,
+/// bond capacity and fee collectors are shared with the configured pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairSettings {
+    /// Liquidity Pool Pair contract address of Astroport-Core
+    pub pool_pair_address: String,
+    /// Liquidity LP token contract address of Astroport-Core
+    pub liquidity_token: String,
+    /// Asset the Fury pool is quoted in, platform fees for this pair are paid in it
+    pub quote_asset: AssetInfo,
+    /// Astroport oracle contract of the pool, bonds are priced from the pool reserves when not set
+    pub oracle_address: Option<String>,
+    /// discount_rate when fury and the quote asset are both provided
+    pub pair_discount_rate: u16,
+    /// bonding period when fury and the quote asset are both provided
+    pub pair_bonding_period_in_sec: u64,
+    /// discount_rate when only the quote asset is provided
+    pub native_discount_rate: u16,
+    /// bonding period when only the quote asset is provided
+    pub native_bonding_period_in_sec: u64,
+    /// Platform Fee Specified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub platform_fees: Uint128,
+    /// Transaction Fee Specified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub transaction_fees: Uint128,
+    /// Swap Fees pecified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub swap_fees: Uint128,
+}
+
+/// Vesting curve governing how the discounted Fury of a bond becomes claimable
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VestingSchedule {
+    /// Whole bonded amount is released once the bonding period is over
+    Cliff,
+    /// Bonded amount is released pro rata over the bonding period
+    Linear,
+    /// Nothing is released during the cliff, the bonded amount is then released
+    /// pro rata over the rest of the bonding period
+    CliffThenLinear { cliff_period_in_sec: u64 },
+}
+
+impl Default for VestingSchedule {
+    fn default() -> Self {
+        VestingSchedule::Cliff
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -60,6 +259,68 @@ pub enum ExecuteMsg {
         ///Time in nano seconds since EPOC when the swapping will be enabled
         swap_opening_date: Uint64,
     },
+    /// Update the Proxy Parameters, only the fields that are set are changed
+    UpdateConfig(Box<UpdateConfigMsg>),
+    /// Creates a proposal to change the admin of the proxy, valid for `expires_in` seconds
+    ProposeNewOwner {
+        /// newly proposed admin address
+        owner: String,
+        /// validity period of the proposal in seconds
+        expires_in: u64,
+    },
+    /// Removes the existing proposal to change the admin of the proxy
+    DropOwnershipProposal {},
+    /// Used by the proposed admin to claim the ownership of the proxy
+    ClaimOwnership {},
+    /// Adds a bond term users can pick when bonding, only the admin can execute this
+    AddBondTerm {
+        bond_type: BondType,
+        /// bonding period of the term in seconds
+        bonding_period_in_sec: u64,
+        /// discount rate of the term, 100% = 10000
+        discount_rate: u16,
+        /// vesting curve of the term, the one of the bond type is used when not set
+        vesting_schedule: Option<VestingSchedule>,
+    },
+    /// Stops offering a bond term, bonds already issued under it are not affected
+    RetireBondTerm { term_id: u64 },
+    /// Registers a Fury pool or replaces its settings, only the admin can execute this
+    SetPair {
+        pair_id: String,
+        settings: PairSettings,
+    },
+    /// Removes a registered Fury pool, rejected while requests are still pending on it
+    RemovePair { pair_id: String },
+    /// Pauses or resumes proxy actions, the guardian can only pause them while the admin can also
+    /// resume them. Actions left out are not changed
+    SetPauseStatus {
+        swaps: Option<bool>,
+        pair_bonding: Option<bool>,
+        native_bonding: Option<bool>,
+        claims: Option<bool>,
+        withdrawals: Option<bool>,
+    },
+    /// Grants or revokes early access, only the admin can execute this. The usage of addresses
+    /// already granted is kept when their cap changes
+    UpdateEarlyAccessAllowlist {
+        add: Vec<EarlyAccessEntry>,
+        remove: Vec<String>,
+    },
+    /// Grants early access to the sender with the cap of its leaf in the early access Merkle tree
+    RegisterEarlyAccess {
+        cap: Uint128,
+        /// Hex encoded sibling hashes from the leaf up to the root
+        proof: Vec<String>,
+    },
+    /// Claims the ASTRO and proxy rewards of the LP tokens staked by the proxy in the generator
+    /// and sends them to pair_lp_tokens_holder, only the admin can execute this
+    HarvestProtocolLiquidity {},
+    /// Withdraws LP tokens staked by the proxy from the generator to pair_lp_tokens_holder along
+    /// with their rewards, only the admin can execute this
+    UnstakeProtocolLiquidity { lp_token: String, amount: Uint128 },
+    /// Rolls back a pending request stuck in the reply chain, reversing any bond already recorded
+    /// and refunding the funds and platform fees of the user
+    CancelPendingRequest { request_id: u64 },
     /// ## Description
     /// Receives a message of type [`Cw20ReceiveMsg`]
     Receive(Cw20ReceiveMsg),
@@ -71,6 +332,12 @@ pub enum ExecuteMsg {
         slippage_tolerance: Option<Decimal>,
         /// Determines whether an autostake will be performed on the generator
         auto_stake: Option<bool>,
+        /// Bond term to apply, the default pair discount and bonding period are used when not set
+        term_id: Option<u64>,
+        /// Registered pool to provide to, the configured pool when not set
+        pair_id: Option<String>,
+        /// Address credited with a share of the platform fees
+        referrer: Option<String>,
     },
     /// ProvideNativeForReward a user provides native liquidity (UST only) and gets Fury rewards
     ProvideNativeForReward {
@@ -80,6 +347,12 @@ pub enum ExecuteMsg {
         slippage_tolerance: Option<Decimal>,
         /// Determines whether an autostake will be performed on the generator
         auto_stake: Option<bool>,
+        /// Bond term to apply, the default native discount and bonding period are used when not set
+        term_id: Option<u64>,
+        /// Registered pool the investment is priced on, the configured pool when not set
+        pair_id: Option<String>,
+        /// Address credited with a share of the platform fees
+        referrer: Option<String>,
     },
     /// ProvideLiquidity an Authorized user provides pair liquidity and gets lp_tokens
     ProvideLiquidity {
@@ -89,6 +362,8 @@ pub enum ExecuteMsg {
         slippage_tolerance: Option<Decimal>,
         /// Determines whether an autostake will be performed on the generator
         auto_stake: Option<bool>,
+        /// Registered pool to provide to, the configured pool when not set
+        pair_id: Option<String>,
     },
     /// Swap an offer asset to the other
     Swap {
@@ -96,72 +371,576 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// Pay the platform fees in Fury instead of the quote asset
+        pay_fees_in_fury: Option<bool>,
+        /// Take the platform fees out of the returned asset instead of attached funds,
+        /// belief_price and max_spread then apply to the amount left after fees
+        deduct_fees_from_proceeds: Option<bool>,
+        /// Registered pool to swap on, the configured pool when not set
+        pair_id: Option<String>,
+        /// Address credited with a share of the platform fees
+        referrer: Option<String>,
+    },
+    /// Buy Fury with any asset listed on the factory. All operations but the last go through the
+    /// router, the last one must swap the quote asset into Fury. Platform fees are taken once,
+    /// out of the quote asset the router returns. Before the opening date, that quote asset is
+    /// counted against the early access allowance of the sender
+    ExecuteSwapOperations {
+        offer_asset: Asset,
+        operations: Vec<SwapOperation>,
+        /// Minimum Fury bought, required as it is the only slippage bound of the whole route
+        minimum_receive: Uint128,
+        to: Option<String>,
+        /// Registered pool of the last operation, the configured pool when not set
+        pair_id: Option<String>,
     },
     /// Claim the Discounted Reward Fury after bond maturity
     RewardClaim {
         receiver: String,
         withdrawal_amount: Uint128,
+        /// Pay the platform fees in Fury instead of the quote asset
+        pay_fees_in_fury: Option<bool>,
+    },
+    /// Claim everything vested so far across all bonds of the sender
+    ClaimAllMatured {
+        /// Pay the platform fees in Fury instead of the quote asset
+        pay_fees_in_fury: Option<bool>,
+    },
+    /// Claim everything vested so far from a single bond of the sender
+    ClaimBond {
+        bond_id: u64,
+        /// Pay the platform fees in Fury instead of the quote asset
+        pay_fees_in_fury: Option<bool>,
     },
+    /// Send the referral rewards credited to the sender
+    ClaimReferralRewards {},
+    /// Release a bond of the sender before maturity, paying a penalty that shrinks until maturity
+    EarlyUnbond {
+        bond_id: u64,
+        /// Pay the platform fees in Fury instead of the quote asset
+        pay_fees_in_fury: Option<bool>,
+    },
+    /// Transfer a bond of the sender to another address, the token id is the bond id
+    TransferNft { recipient: String, token_id: String },
+    /// Transfer a bond of the sender to a contract and trigger an action on the receiving contract
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    /// Allow the spender to transfer a bond of the sender until the approval expires
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    /// Remove the approval of the spender on a bond of the sender
+    Revoke { spender: String, token_id: String },
+    /// Allow the operator to transfer and approve all bonds of the sender until the approval expires
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    /// Remove the approval of the operator on the bonds of the sender
+    RevokeAll { operator: String },
+}
+
+/// Proxy Parameters that can be changed by the admin, unset fields are left unchanged
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct UpdateConfigMsg {
+    /// discount_rate when fury and UST are both provided
+    pub pair_discount_rate: Option<u16>,
+    /// bonding period when fury and UST are both provided
+    pub pair_bonding_period_in_sec: Option<u64>,
+    /// Fury tokens for balanced investment will be fetched from this wallet
+    pub pair_fury_reward_wallet: Option<String>,
+    /// The LP tokens generated at time of Pair investment for discounted Fury Rewards will be assigned to this wallet
+    pub pair_lp_tokens_holder: Option<String>,
+    /// Vesting curve for new pair bonds
+    pub pair_vesting_schedule: Option<VestingSchedule>,
+
+    /// discount_rate when only UST is provided
+    pub native_discount_rate: Option<u16>,
+    /// bonding period when only UST is provided
+    pub native_bonding_period_in_sec: Option<u64>,
+    /// Fury tokens for native(UST only) investment will be fetched from this wallet
+    pub native_investment_reward_wallet: Option<String>,
+    /// The native(UST only) investment will be stored into this wallet
+    pub native_investment_receive_wallet: Option<String>,
+    /// Vesting curve for new native bonds
+    pub native_vesting_schedule: Option<VestingSchedule>,
+
+    /// This address has the authority to provide liquidity (balanced UST + Fury) and in return shall get the LP tokens
+    pub authorized_liquidity_provider: Option<String>,
+    /// The wallet to which various fees that is collected shall be transferred
+    pub platform_fees_collector_wallet: Option<String>,
+    /// Platform Fee Specified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub platform_fees: Option<Uint128>,
+    /// Transaction Fee Specified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub transaction_fees: Option<Uint128>,
+    /// Swap Fees pecified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub swap_fees: Option<Uint128>,
+    /// Maximum number of simultaneous outstanding Bonds of discounted Reward Fury Tokens permitted per user
+    pub max_bonding_limit_per_user: Option<u64>,
+    /// Astroport oracle contract of the pool, bonds are priced from its TWAP when set
+    pub oracle_address: Option<String>,
+    /// Maximum relative deviation of the spot price from the TWAP for a bond to be accepted
+    pub max_twap_deviation: Option<Decimal>,
+    /// Removes the oracle so that bonds are priced from the pool reserves again
+    pub disable_twap_pricing: Option<bool>,
+    /// Issuance limits for pair bonds
+    pub pair_bond_capacity: Option<BondCapacityConfig>,
+    /// Issuance limits for native bonds
+    pub native_bond_capacity: Option<BondCapacityConfig>,
+    /// Enables or disables the transfer of bonds
+    pub bonds_transferable: Option<bool>,
+    /// Maximum penalty of an early unbond, 100% = 10000
+    pub early_unbond_penalty_rate: Option<u16>,
+    /// Disables early unbonds
+    pub disable_early_unbond: Option<bool>,
+    /// Where the Fury forfeited by early unbonds is sent
+    pub early_unbond_penalty_destination: Option<PenaltyDestination>,
+    /// Discount on platform fees paid in Fury, 100% = 10000
+    pub fury_fee_discount: Option<u16>,
+    /// Weighted recipients of the platform fees, an empty list sends all to the collector wallet
+    pub fee_collectors: Option<Vec<FeeCollector>>,
+    /// Astroport maker contract that can be given a share of the platform fees
+    pub maker_address: Option<String>,
+    /// Astroport router used to buy Fury with other assets
+    pub router_address: Option<String>,
+    /// Address allowed to pause the proxy actions in an emergency
+    pub guardian_address: Option<String>,
+    /// Removes the guardian so that only the admin can pause the proxy actions
+    pub remove_guardian: Option<bool>,
+    /// Share of the platform fees credited to the referrer of a swap or bond, 100% = 10000
+    pub referral_fee_share: Option<u16>,
+    /// Seconds before the swap opening date during which early access addresses can swap,
+    /// zero disables early access
+    pub early_access_window_in_sec: Option<u64>,
+    /// Hex encoded Merkle root of the early access addresses and their caps, each leaf is the
+    /// SHA-256 of `{address},{cap}` with the cap in the smallest unit of the quote asset
+    pub early_access_merkle_root: Option<String>,
+    /// Removes the Merkle root so that only the allowlist grants early access
+    pub remove_early_access_merkle_root: Option<bool>,
+    /// Anti-whale limits on swaps
+    pub swap_limits: Option<SwapLimits>,
+    /// Astroport generator the proxy stakes the LP tokens of pair bonds in, it cannot be changed
+    /// while LP tokens are staked. The stake is held by the proxy as the beneficiary of the
+    /// deposits, not by pair_lp_tokens_holder
+    pub generator_address: Option<String>,
+    /// Removes the generator so that the LP tokens of pair bonds are delivered to
+    /// pair_lp_tokens_holder
+    pub remove_generator: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    /// Returns information about Proxy Configuration
-    Configuration {},
+    /// Returns information about Proxy Configuration, with the settings of the pair applied when given
+    Configuration { pair_id: Option<String> },
     /// Returns counts of Tokens related to the Liquidity Pool
-    Pool {},
+    Pool { pair_id: Option<String> },
     /// Returns information about Tokens Paired in the Liquidity Pool
-    Pair {},
+    Pair { pair_id: Option<String> },
     /// Returns information about the simulation of the swap in a [`SimulationResponse`] object.
     Simulation {
         offer_asset: Asset,
+        pair_id: Option<String>,
     },
     /// Returns information about the reverse simulation in a [`ReverseSimulationResponse`] object.
     ReverseSimulation {
         ask_asset: Asset,
+        pair_id: Option<String>,
     },
     /// Returns information about the cumulative prices in a [`CumulativePricesResponse`] object
-    CumulativePrices {},
-    /// Returns Timestamp after which Swap operations would be permitted in the Liquidity Pool
-    GetSwapOpeningDate {},
-    /// Returns status of Fury Reward Tokens Bonded or allocated at discounted rate against Native or Pair Investment
-    GetBondingDetails {
-        user_address: String,
+    CumulativePrices { pair_id: Option<String> },
+    /// Returns the registered Fury pools in a list of [`PairResponse`] objects
+    Pairs {},
+    /// Returns the proxy actions currently paused in a [`PauseStatus`] object
+    Status {},
+    /// Returns the referral rewards of a referrer in a [`ReferralRewardsResponse`] object
+    ReferralRewards { referrer: String },
+    /// Returns the early access eligibility and remaining allowance of an address in a
+    /// [`EarlyAccessResponse`] object. An address not registered yet can pass its Merkle leaf
+    EarlyAccess {
+        address: String,
+        cap: Option<Uint128>,
+        proof: Option<Vec<String>>,
+    },
+    /// Returns the referral rewards of all referrers in ascending order of address
+    Referrers {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
+    /// Returns the LP tokens staked in the generator by the proxy in a list of
+    /// [`ProtocolLiquidityResponse`] objects
+    ProtocolLiquidity {},
+    /// Returns Timestamp after which Swap operations would be permitted, the date is shared by all
+    /// registered pools
+    GetSwapOpeningDate {},
+    /// Returns status of Fury Reward Tokens Bonded or allocated at discounted rate against Native or Pair Investment,
+    /// covering the bonds of the user on all registered pools
+    GetBondingDetails { user_address: String },
+    /// Returns the amount of bonded Fury the user can claim right now
+    GetClaimableAmount { user_address: String },
     /// Returns Fury Equivalent for some UST amount (without operational overheads of swap)
     GetFuryEquivalentToUst {
         ust_count: Uint128,
+        pair_id: Option<String>,
     },
     /// Returns UST Equivalent for some Fury amount (without operational overheads of swap)
     GetUstEquivalentToFury {
         fury_count: Uint128,
+        pair_id: Option<String>,
+    },
+    /// Returns the bond terms, including retired ones
+    BondTerms {},
+    /// Returns the current owner of a bond and its approvals in a [`OwnerOfResponse`] object
+    OwnerOf {
+        token_id: String,
+        /// Include expired approvals, false by default
+        include_expired: Option<bool>,
+    },
+    /// Returns the name and symbol of the bond tokens in a [`ContractInfoResponse`] object
+    ContractInfo {},
+    /// Returns the details of a bond in a [`NftInfoResponse`] object
+    NftInfo { token_id: String },
+    /// Returns the owner, approvals and details of a bond in a [`AllNftInfoResponse`] object
+    AllNftInfo {
+        token_id: String,
+        /// Include expired approvals, false by default
+        include_expired: Option<bool>,
+    },
+    /// Returns the bonds owned by an address in a [`TokensResponse`] object
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the number of outstanding bonds in a [`NumTokensResponse`] object
+    NumTokens {},
+    /// Returns all outstanding bonds in ascending order of bond id
+    AllBonds {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the outstanding and matured Fury and the UST raised per bond type in a [`BondStatisticsResponse`] object
+    BondStatistics {},
+    /// Returns the outstanding Fury per day of maturity in ascending order of day
+    MaturityHistogram {
+        /// Day since epoch, i.e. timestamp in seconds divided by 86400
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the remaining bond issuance capacity for pair and native bonds, the capacity is
+    /// shared by all registered pools
+    BondCapacity {},
+    /// Returns requests that are still going through the reply chain
+    PendingRequests {
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
     /// Returns Platform Fee required for specific ExecuteMsg
     QueryPlatformFees {
         msg: Binary,
+        /// Address of the sender, required to price claims against its bonds
+        user_address: Option<String>,
+    },
+    /// Returns the Platform Fee required for specific ExecuteMsg when it is paid in Fury
+    QueryPlatformFeesInFury {
+        msg: Binary,
+        /// Address of the sender, required to price claims against its bonds
+        user_address: Option<String>,
+    },
+    /// Previews a ProvidePairForReward of the user in a [`SimulateBondResponse`] object
+    SimulateProvidePairForReward {
+        user_address: String,
+        assets: [Asset; 2],
+        term_id: Option<u64>,
+        pair_id: Option<String>,
+    },
+    /// Previews a ProvideNativeForReward of the user in a [`SimulateBondResponse`] object
+    SimulateProvideNativeForReward {
+        user_address: String,
+        asset: Asset,
+        term_id: Option<u64>,
+        pair_id: Option<String>,
+    },
+    /// Previews a Swap in a [`SimulateSwapResponse`] object
+    SimulateSwap {
+        offer_asset: Asset,
+        pay_fees_in_fury: Option<bool>,
+        deduct_fees_from_proceeds: Option<bool>,
+        pair_id: Option<String>,
     },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ProxyCw20HookMsg {
-    // ProvideLiquidity {
-    //     /// the type of asset available in [`Asset`]
-    //     assets: [Asset; 2],
-    //     /// the slippage tolerance for sets the maximum percent of price movement
-    //     slippage_tolerance: Option<Decimal>,
-    //     /// Determines whether an autostake will be performed on the generator
-    //     auto_stake: Option<bool>,
-    //     /// the receiver of provide liquidity
-    //     receiver: Option<String>,
-    // },
-    /// Sell a given amount of asset
-    // Swap {
-    //     belief_price: Option<Decimal>,
-    //     max_spread: Option<Decimal>,
-    //     to: Option<String>,
-    // },
-    /// Withdrawing liquidity from the pool against the LP Tokens
-    WithdrawLiquidity {},
+    /// The authorized liquidity provider provides the sent Fury as pair liquidity. No native
+    /// funds can be attached to a CW20 send, so the pair must be quoted in a CW20 token, which
+    /// is pulled from the provider through its allowance to the proxy. Pairs quoted in a native
+    /// asset are rejected with NativeQuoteAssetNotSupported, their liquidity is provided through
+    /// [`ExecuteMsg::ProvideLiquidity`] with the native side attached as funds
+    ProvideLiquidity {
+        /// the type of asset available in [`Asset`]
+        assets: [Asset; 2],
+        /// the slippage tolerance for sets the maximum percent of price movement
+        slippage_tolerance: Option<Decimal>,
+        /// Determines whether an autostake will be performed on the generator
+        auto_stake: Option<bool>,
+        /// the receiver of provide liquidity, the provider when not set
+        receiver: Option<String>,
+        /// Registered pool to provide liquidity to, the configured pool when not set
+        pair_id: Option<String>,
+    },
+    /// Sell the sent Fury, the platform fees are taken out of the proceeds of the sale
+    Swap {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// Registered pool to swap on, the configured pool when not set
+        pair_id: Option<String>,
+    },
+    /// Withdrawing liquidity from the pool against the LP Tokens, the platform fees are taken out
+    /// of the refunded assets
+    WithdrawLiquidity {
+        /// Minimum amounts of the pool assets to receive after the platform fees
+        min_assets: Option<Vec<Asset>>,
+        /// Address the refunded assets are sent to, the sender when not set
+        receiver: Option<String>,
+    },
+}
+
+/// Remaining issuance capacity of a bond type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondCapacityInfo {
+    /// Start of the current epoch in seconds
+    pub epoch_start: u64,
+    /// Fury bonded in the current epoch
+    pub bonded_in_epoch: Uint128,
+    /// Fury that can still be bonded in the current epoch, None when unlimited
+    pub remaining_in_epoch: Option<Uint128>,
+    /// Fury bonded over the whole programme
+    pub total_bonded: Uint128,
+    /// Fury that can still be bonded over the programme, None when unlimited
+    pub remaining_budget: Option<Uint128>,
+    /// Fury the reward wallet can still provide to the proxy
+    pub reward_wallet_available: Uint128,
+}
+
+/// This structure describes the proxy actions paused by the guardian or the admin
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseStatus {
+    /// Swaps through the proxy, including the CW20 hook and routed swaps
+    pub swaps: bool,
+    /// Bonds issued for pair investments
+    pub pair_bonding: bool,
+    /// Bonds issued for native investments
+    pub native_bonding: bool,
+    /// Claims of bonded Fury, including early unbonds
+    pub claims: bool,
+    /// Withdrawal of liquidity through the CW20 hook
+    pub withdrawals: bool,
+}
+
+/// This structure describes the bond a pair or native investment would get
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateBondResponse {
+    /// Platform fees in the quote asset, attached along with the investment
+    pub platform_fees: Uint128,
+    pub bond_type: BondType,
+    /// Fury bonded
+    pub fury_amount: Uint128,
+    /// Discount of the bond type or bond term, 100% = 10000
+    pub discount_rate: u16,
+    /// Discount left once the platform fees are paid, against the pool price of the bonded Fury
+    pub effective_discount: Decimal,
+    pub bonding_period_in_sec: u64,
+    /// Time the bond fully vests, counted from the swap opening date before it
+    pub maturity: Timestamp,
+    pub vesting_schedule: VestingSchedule,
+    /// The user already holds max_bonding_limit_per_user bonds and cannot bond again
+    pub bond_limit_reached: bool,
+}
+
+/// This structure describes the outcome of a swap through the proxy
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateSwapResponse {
+    /// Platform fees attached in the quote asset
+    pub platform_fees: Uint128,
+    /// Platform fees pulled in Fury
+    pub fury_fees: Uint128,
+    /// Amount returned by the pool, before taxes
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+    /// Amount received once the platform fees deducted from the proceeds are taken out
+    pub net_return_amount: Uint128,
+}
+
+/// Early access granted to an address, in quote asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyAccessEntry {
+    pub address: String,
+    /// Maximum quote asset value swapped before the opening date
+    pub cap: Uint128,
+}
+
+/// This structure describes the early access of an address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyAccessResponse {
+    /// Address can swap during the early access window
+    pub eligible: bool,
+    /// Start of the early access window, early access is disabled when None
+    pub window_start: Option<Timestamp>,
+    pub cap: Uint128,
+    pub used: Uint128,
+    pub remaining: Uint128,
+}
+
+/// This structure describes the platform fees credited to a referrer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferralRewardsResponse {
+    pub referrer: String,
+    /// Rewards not claimed yet
+    pub claimable: Vec<Asset>,
+    /// Rewards credited since the first referral
+    pub total_earned: Vec<Asset>,
+}
+
+/// This structure describes the LP tokens of a pool staked in the generator by the proxy
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProtocolLiquidityResponse {
+    pub lp_token: String,
+    pub amount: Uint128,
+}
+
+/// This structure describes a registered Fury pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairResponse {
+    pub pair_id: String,
+    pub settings: PairSettings,
+}
+
+/// This structure describes an address allowed to transfer a bond
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Approval {
+    pub spender: String,
+    pub expires: Expiration,
+}
+
+/// This structure describes the owner of a bond
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerOfResponse {
+    pub owner: String,
+    /// Addresses allowed to transfer the bond besides the owner and its operators
+    pub approvals: Vec<Approval>,
+}
+
+/// This structure describes the bond tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractInfoResponse {
+    pub name: String,
+    pub symbol: String,
+}
+
+/// This structure describes a bond as a CW721 token, the bond details are the token extension
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftInfoResponse<T> {
+    /// Bonds have no off-chain metadata
+    pub token_uri: Option<String>,
+    pub extension: T,
+}
+
+/// This structure describes the owner, approvals and details of a bond
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllNftInfoResponse<T> {
+    pub access: OwnerOfResponse,
+    pub info: NftInfoResponse<T>,
+}
+
+/// This structure describes the bonds of an owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokensResponse {
+    /// Bond ids as token ids
+    pub tokens: Vec<String>,
+}
+
+/// This structure describes the number of outstanding bonds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NumTokensResponse {
+    pub count: u64,
+}
+
+/// Message sent to the receiving contract of a SendNft
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+impl Cw721ReceiveMsg {
+    /// Serializes the message wrapped into ReceiveNft
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = ReceiverExecuteMsg::ReceiveNft(self);
+        to_binary(&msg)
+    }
+
+    /// Creates a cosmos message executing ReceiveNft on the receiving contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// Execute message the receiving contract of a SendNft has to implement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ReceiverExecuteMsg {
+    ReceiveNft(Cw721ReceiveMsg),
+}
+
+/// Statistics of a bond type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondStatisticsInfo {
+    /// Number of bonds not fully claimed yet
+    pub outstanding_bonds: u64,
+    /// Bonded Fury not claimed yet
+    pub outstanding_fury: Uint128,
+    /// Part of outstanding_fury whose bonds have reached their maturity day
+    pub matured_fury: Uint128,
+    /// UST raised through bonds
+    pub ust_raised: Uint128,
+}
+
+/// This structure describes a bond statistics query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondStatisticsResponse {
+    pub pair: BondStatisticsInfo,
+    pub native: BondStatisticsInfo,
+}
+
+/// Outstanding Fury maturing on a day
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaturityHistogramEntry {
+    /// Day since epoch
+    pub day: u64,
+    pub pair: Uint128,
+    pub native: Uint128,
+}
+
+/// This structure describes a bond capacity query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondCapacityResponse {
+    pub pair: BondCapacityInfo,
+    pub native: BondCapacityInfo,
 }
@@ -1,7 +1,18 @@
-use cosmwasm_std::{Addr, Binary, Coin, Timestamp, Uint128};
-use cw_storage_plus::{Bound, Item, Map};
+use crate::msg::{
+    Approval, BondCapacityConfig, BondType, FeeCollector, PairSettings, PauseStatus,
+    PenaltyDestination, SwapLimits, VestingSchedule,
+};
+use astroport::asset::{Asset, AssetInfo};
+use astroport::common::OwnershipProposal;
+use cosmwasm_std::{
+    Addr, Binary, Coin, Decimal, Order, StdError, StdResult, Storage, Timestamp, Uint128,
+};
+use cw20::Expiration;
+use cw_storage_plus::{Bound, Item, Map, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -9,7 +20,7 @@ pub struct Config {
     pub admin_address: Addr,
     /// contract address of Fury token
     pub custom_token_address: Addr,
-    
+
     /// discount_rate when fury and UST are both provided
     pub pair_discount_rate: u16,
     /// bonding period when fury and UST are both provided in seconds
@@ -28,7 +39,7 @@ pub struct Config {
     /// Fury tokens for native(UST only) investment will be fetched from this wallet
     pub native_investment_reward_wallet: Addr,
     /// The native(UST only) investment will be stored into this wallet
-    pub native_investment_receive_wallet: Addr,    
+    pub native_investment_receive_wallet: Addr,
 
     /// This address has the authority to pump in liquidity
     /// The LP tokens for this address will be returned to this address
@@ -36,7 +47,7 @@ pub struct Config {
 
     ///Time in nano seconds since EPOC when the swapping will be enabled
     pub swap_opening_date: Timestamp,
-    
+
     /// Pool pair address from liquidity provider (astroport)
     pub pool_pair_address: String,
     /// LP token contract address
@@ -50,10 +61,87 @@ pub struct Config {
     ///Specified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
     pub swap_fees: Uint128,
     pub max_bonding_limit_per_user: u64,
+    /// Vesting curve applied to new pair bonds
+    pub pair_vesting_schedule: VestingSchedule,
+    /// Vesting curve applied to new native bonds
+    pub native_vesting_schedule: VestingSchedule,
+    /// Astroport oracle contract used to price bonds from the TWAP instead of the spot reserves
+    pub oracle_address: Option<Addr>,
+    /// Bonds are rejected when the spot price deviates from the TWAP by more than this ratio
+    pub max_twap_deviation: Option<Decimal>,
+    /// Issuance limits for pair bonds
+    pub pair_bond_capacity: BondCapacityConfig,
+    /// Issuance limits for native bonds
+    pub native_bond_capacity: BondCapacityConfig,
+    /// Bonds can be transferred as CW721 tokens by their owner
+    pub bonds_transferable: bool,
+    /// Maximum penalty of an early unbond, specified in percentage multiplied by 100, early unbonds are disabled when None
+    pub early_unbond_penalty_rate: Option<u16>,
+    /// Where the Fury forfeited by early unbonds is sent
+    pub early_unbond_penalty_destination: PenaltyDestination,
+    /// Asset the Fury pool is quoted in, platform fees are paid in it
+    pub quote_asset: AssetInfo,
+    /// Discount on platform fees paid in Fury, 100% = 10000
+    pub fury_fee_discount: u16,
+    /// Weighted recipients of the platform fees, all fees go to the collector wallet when empty
+    pub fee_collectors: Vec<FeeCollector>,
+    /// Astroport maker contract that can be given a share of the platform fees
+    pub maker_address: Option<Addr>,
+    /// Astroport router used to buy Fury with other assets
+    pub router_address: Option<Addr>,
+    /// Address allowed to pause the proxy actions along with the admin
+    pub guardian_address: Option<Addr>,
+    /// Share of the platform fees credited to referrers, 100% = 10000
+    pub referral_fee_share: u16,
+    /// Seconds before the swap opening date during which early access addresses can swap,
+    /// early access is disabled when zero
+    pub early_access_window_in_sec: u64,
+    /// Hex encoded Merkle root of the early access addresses and their caps, each leaf is the
+    /// SHA-256 of `{address},{cap}` with the cap in the smallest unit of the quote asset
+    pub early_access_merkle_root: Option<String>,
+    /// Anti-whale limits on swaps
+    pub swap_limits: SwapLimits,
+    /// Astroport generator the proxy stakes the LP tokens of pair bonds in, the LP tokens are
+    /// delivered to pair_lp_tokens_holder when not set. The proxy holds the stake as the
+    /// beneficiary of the deposits, pair_lp_tokens_holder only receives the rewards and the LP
+    /// tokens unstaked by the admin
+    pub generator_address: Option<Addr>,
 }
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
 
+/// Map of registered Fury pools and their settings, the key is the pair id
+pub const PAIRS: Map<&str, PairSettings> = Map::new("pairs");
+
+/// Proxy actions paused by the guardian or the admin, nothing is paused when not saved
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
+/// Loads the configuration with the settings of the given pair applied, the configured pool is
+/// used when no pair is given
+pub fn load_config(storage: &dyn Storage, pair_id: Option<&str>) -> StdResult<Config> {
+    let mut config = CONFIG.load(storage)?;
+    if let Some(pair_id) = pair_id {
+        let pair = PAIRS
+            .may_load(storage, pair_id)?
+            .ok_or_else(|| StdError::not_found(format!("pair {}", pair_id)))?;
+        config.pool_pair_address = pair.pool_pair_address;
+        config.liquidity_token = Addr::unchecked(pair.liquidity_token);
+        config.quote_asset = pair.quote_asset;
+        config.oracle_address = pair.oracle_address.map(Addr::unchecked);
+        config.pair_discount_rate = pair.pair_discount_rate;
+        config.pair_bonding_period_in_sec = pair.pair_bonding_period_in_sec;
+        config.native_discount_rate = pair.native_discount_rate;
+        config.native_bonding_period_in_sec = pair.native_bonding_period_in_sec;
+        config.platform_fees = pair.platform_fees;
+        config.transaction_fees = pair.transaction_fees;
+        config.swap_fees = pair.swap_fees;
+    }
+    Ok(config)
+}
+
+/// Pending proposal to transfer the admin role of the proxy
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+
 pub const CONTRACT: Item<ContractVersion> = Item::new("contract_info");
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -75,6 +163,10 @@ pub enum SubMessageType {
     TransferFromSubMsg,
     IncreaseAlowanceSubMsg,
     ProvideLiquiditySubMsg,
+    SwapSubMsg,
+    RouteSubMsg,
+    WithdrawLiquiditySubMsg,
+    GeneratorSubMsg,
 }
 
 /// This is used for saving pending request details
@@ -87,6 +179,11 @@ pub enum SubMessageNextAction {
     ProvideLiquidity,
     TransferCustomAssetsFromFundsOwner,
     TransferToNativeInvestmentReceiveWallet,
+    CompletePendingRequest,
+    ForwardSwapProceeds,
+    SwapToFury,
+    ForwardWithdrawnAssets,
+    ForwardProxyRewards,
 }
 
 /// This is used for saving pending request details
@@ -105,33 +202,473 @@ pub struct SubMessageDetails {
 
     pub funds: Vec<Coin>,
 
-	pub user_address: String,
+    pub user_address: String,
 
-	pub is_fury_provided: bool,
+    pub is_fury_provided: bool,
+
+    /// Bond term picked by the user
+    #[serde(default)]
+    pub term_id: Option<u64>,
+
+    /// id of the pending request this submessage is a step of
+    #[serde(default)]
+    pub request_id: u64,
 }
 /// Map of request and list of their bonds. the key is request id and the
 /// Value jsonified request
 pub const SUB_MESSAGE_DETAILS: Map<String, SubMessageDetails> = Map::new("pending_request_details");
 
+/// Swap whose platform fees are taken out of the returned asset, saved as submessage payload
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SwapProceedsDetails {
+    /// Address the returned asset is forwarded to
+    pub receiver: String,
+    pub offer_amount: Uint128,
+    pub ask_asset_info: AssetInfo,
+    /// Platform fees in the ask asset
+    pub platform_fees: Uint128,
+    pub belief_price: Option<Decimal>,
+    pub max_spread: Option<Decimal>,
+    /// Registered pool the swap was made on
+    #[serde(default)]
+    pub pair_id: Option<String>,
+    /// Address credited with a share of the platform fees
+    #[serde(default)]
+    pub referrer: Option<Addr>,
+}
+
+/// Liquidity withdrawal whose platform fees are taken out of the refunded assets, saved as
+/// submessage payload
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct WithdrawLiquidityDetails {
+    /// Address the refunded assets are forwarded to
+    pub receiver: String,
+    /// Minimum amounts of the pool assets to forward
+    pub min_assets: Vec<Asset>,
+    /// Registered pool the liquidity is withdrawn from
+    pub pair_id: Option<String>,
+}
+
+/// Generator call that can pay the proxy rewards of the staked LP tokens, saved as submessage
+/// payload
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ProxyRewardDetails {
+    /// Address the proxy rewards are forwarded to
+    pub recipient: String,
+    /// Proxy reward token of the pool
+    pub reward_token: String,
+    /// Balance of the proxy in the reward token before the generator call
+    pub prev_balance: Uint128,
+}
+
+/// Route swapped through the router before the Fury hop, saved as submessage payload
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct RouteSwapDetails {
+    /// Address the bought Fury is sent to
+    pub receiver: String,
+    /// Quote asset balance of the proxy before the route was executed
+    pub prev_balance: Uint128,
+    /// Minimum Fury bought, checked by the pair on the Fury hop
+    pub minimum_receive: Uint128,
+    /// Registered pool of the Fury hop
+    #[serde(default)]
+    pub pair_id: Option<String>,
+}
+
+/// Step of the reply chain a pending request is waiting for
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingRequestStatus {
+    /// Fury of the user is being transferred to the proxy
+    #[serde(alias = "awaiting_user_transfer")]
+    UserTransfer,
+    /// Bonded Fury is being transferred from the reward wallet to the proxy
+    #[serde(alias = "awaiting_treasury_transfer")]
+    TreasuryTransfer,
+    /// Allowance of the pair on the Fury held by the proxy is being increased
+    #[serde(alias = "awaiting_allowance")]
+    Allowance,
+    /// Liquidity is being provided to the pair
+    #[serde(alias = "awaiting_provide_liquidity")]
+    ProvideLiquidity,
+}
+
+/// This is used for tracking a request across the steps of the reply chain,
+/// so that it can be rolled back if one of them fails
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingRequest {
+    pub request_id: u64,
+
+    /// Address of the user wallet that initiated the request
+    pub user_address: String,
+
+    pub status: PendingRequestStatus,
+
+    /// id of the submessage currently dispatched for this request
+    pub sub_req_id: u64,
+
+    /// Native funds held by the proxy for this request
+    pub funds: Vec<Coin>,
+
+    /// Fury moved from the user wallet to the proxy
+    pub user_fury_amount: Uint128,
+
+    /// Reward wallet the bonded Fury is fetched from
+    pub treasury_address: Option<Addr>,
+
+    /// Bonded Fury moved from the reward wallet to the proxy
+    pub treasury_fury_amount: Uint128,
+
+    /// Bond recorded for this request
+    pub bond_id: Option<u64>,
+
+    /// UST raised by the bond of this request
+    #[serde(default)]
+    pub ust_amount: Uint128,
+
+    /// CW20 quote tokens pulled from the user to provide liquidity
+    #[serde(default)]
+    pub quote_token_amount: Uint128,
+
+    /// Registered pool the request is made on, the configured pool when None
+    #[serde(default)]
+    pub pair_id: Option<String>,
+
+    /// Address credited with a share of the platform fees of the request
+    #[serde(default)]
+    pub referrer: Option<Addr>,
+
+    /// Platform fees of the request, collected once the request completes
+    #[serde(default)]
+    pub platform_fees: Uint128,
+
+    /// Share of the platform fees credited to the referrer once the request completes
+    #[serde(default)]
+    pub referral_fees: Uint128,
+}
+/// Map of pending requests, the key is the request id
+pub const PENDING_REQUESTS: Map<U64Key, PendingRequest> = Map::new("pending_requests");
+
+pub const PENDING_REQUEST_ID: Item<u64> = Item::new("pending_request_id");
 
 /// This is used for saving various bond details
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct BondedRewardsDetails {
+    /// Unique id of the bond, stable for its whole lifetime
+    #[serde(default)]
+    pub bond_id: u64,
+
     /// Address of the user wallet
     pub user_address: String,
 
+    /// Kind of investment the bond was issued for
+    #[serde(default)]
+    pub bond_type: BondType,
+
     /// reward amount acrrued for this bond in quantity of tokens
     pub bonded_amount: Uint128,
 
     pub bonding_period: u64,
 
-    pub bonding_start_timestamp : Timestamp,
+    pub bonding_start_timestamp: Timestamp,
+
+    /// Vesting curve governing how the bonded amount is released
+    #[serde(default)]
+    pub vesting_schedule: VestingSchedule,
+
+    /// Fury already claimed out of bonded_amount
+    #[serde(default)]
+    pub claimed_amount: Uint128,
+
+    /// Bond term the bond was issued under, if any
+    #[serde(default)]
+    pub term_id: Option<u64>,
 }
-/// Map of users and list of their bonded rewards. the key is user name and the
+/// Map of bonds, the key is the address of the owner and the bond id.
 /// BondedRewardDetails will contain information about the users and rewards
-pub const BONDED_REWARDS_DETAILS: Map<String, Vec<BondedRewardsDetails>> =
-    Map::new("bonded_rewards_details");
+pub const BONDED_REWARDS_DETAILS: Map<(&str, U64Key), BondedRewardsDetails> = Map::new("bonds");
+
+/// Map of bonds and their current owner, the key is the bond id
+pub const BOND_OWNERS: Map<U64Key, String> = Map::new("bond_owners");
 
+/// Map of the addresses allowed to transfer a bond, the key is the bond id.
+/// The approvals are cleared when the bond changes owner or is removed
+pub const BOND_APPROVALS: Map<U64Key, Vec<Approval>> = Map::new("bond_approvals");
+
+/// Map of the operators allowed to transfer and approve all bonds of an owner,
+/// the key is the owner and the operator
+pub const BOND_OPERATORS: Map<(&str, &str), Expiration> = Map::new("bond_operators");
+
+/// This is used for tracking the outstanding bonds and the UST raised by a bond type
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct BondStats {
+    /// Number of bonds not fully claimed yet
+    pub outstanding_bonds: u64,
+    /// Bonded Fury not claimed yet
+    pub outstanding_fury: Uint128,
+    /// UST raised through bonds
+    pub ust_raised: Uint128,
+}
+/// Map of bond types and their statistics, the key is the bond type name
+pub const BOND_STATS: Map<String, BondStats> = Map::new("bond_stats");
+
+/// This is used for tracking the outstanding Fury maturing at the same time
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct MaturityBucket {
+    pub pair: Uint128,
+    pub native: Uint128,
+}
+
+impl MaturityBucket {
+    pub fn add(&mut self, other: &MaturityBucket) {
+        self.pair += other.pair;
+        self.native += other.native;
+    }
+}
+/// Map of days and the outstanding Fury maturing on them, the key is the day since epoch
+pub const MATURITY_HISTOGRAM: Map<U64Key, MaturityBucket> = Map::new("maturity_histogram");
+/// Map of bonding periods and the outstanding Fury of bonds issued before the swap opening,
+/// these mature relative to the swap opening date which can still change
+pub const PRE_OPENING_MATURITIES: Map<U64Key, MaturityBucket> = Map::new("pre_opening_maturities");
+
+/// This is used for saving the bond terms offered to users
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct BondTerm {
+    pub term_id: u64,
+
+    pub bond_type: BondType,
+
+    pub bonding_period_in_sec: u64,
+
+    /// Specified in percentage multiplied by 100, i.e. 100% = 10000 and 0.01% = 1
+    pub discount_rate: u16,
+
+    /// Vesting curve of the term, the one configured for the bond type is used when not set
+    pub vesting_schedule: Option<VestingSchedule>,
+
+    /// Retired terms can no longer be picked for new bonds
+    pub active: bool,
+}
+/// Map of bond terms, the key is the term id
+pub const BOND_TERMS: Map<U64Key, BondTerm> = Map::new("bond_terms");
+
+pub const BOND_TERM_ID: Item<u64> = Item::new("bond_term_id");
+
+/// This is used for tracking the Fury issued for a bond type against its capacity
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct BondCapacityUsage {
+    /// Start of the epoch bonded_in_epoch refers to, in seconds
+    pub epoch_start: u64,
+    /// Fury bonded in the epoch
+    pub bonded_in_epoch: Uint128,
+    /// Fury bonded over the whole programme
+    pub total_bonded: Uint128,
+}
+/// Map of bond types and their issued Fury, the key is the bond type name
+pub const BOND_CAPACITY_USAGE: Map<String, BondCapacityUsage> = Map::new("bond_capacity_usage");
+
+/// Fury bought or sold by a swap, the time is in seconds
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SwapRecord {
+    pub time: u64,
+    pub fury_amount: Uint128,
+}
+
+/// This is used for tracking the swaps of an address against the swap limits
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct SwapActivity {
+    /// Swaps of the rolling window, the last swap is kept for the cooldown
+    pub swaps: Vec<SwapRecord>,
+}
+/// Map of swap activity, the key is the address
+pub const SWAP_ACTIVITY: Map<&str, SwapActivity> = Map::new("swap_activity");
 
 pub const SUB_REQ_ID: Item<u64> = Item::new("sub_req_id");
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Id of the most recently recorded bond
+pub const BOND_ID: Item<u64> = Item::new("bond_id");
+
+/// Quote asset value an address can swap before the opening date
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct EarlyAccessAllowance {
+    pub cap: Uint128,
+    pub used: Uint128,
+}
+/// Map of early access allowances, the key is the address
+pub const EARLY_ACCESS_ALLOWANCES: Map<&str, EarlyAccessAllowance> =
+    Map::new("early_access_allowances");
+
+/// Platform fees credited to a referrer, one entry per asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ReferralRewards {
+    /// Rewards not claimed yet
+    pub claimable: Vec<Asset>,
+    /// Rewards credited since the first referral
+    pub total_earned: Vec<Asset>,
+}
+/// Map of referral rewards, the key is the referrer address
+pub const REFERRAL_REWARDS: Map<&str, ReferralRewards> = Map::new("referral_rewards");
+
+/// LP tokens of pair bonds staked in the generator by the proxy, the key is the LP token address
+pub const PROTOCOL_LIQUIDITY: Map<&str, Uint128> = Map::new("protocol_liquidity");
+
+//settings for pagination
+/// The maximum limit for paginated reads
+const MAX_LIMIT: u32 = 30;
+
+/// The default limit for paginated reads
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Reads pending requests in ascending order of request id, starting after `start_after`
+pub fn read_pending_requests(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PendingRequest>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|request_id| Bound::exclusive(U64Key::new(request_id)));
+
+    PENDING_REQUESTS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, request) = item?;
+            Ok(request)
+        })
+        .collect()
+}
+
+/// Reads referral rewards in ascending order of referrer address, starting after `start_after`
+pub fn read_referral_rewards(
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, ReferralRewards)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|referrer| Bound::exclusive(referrer.as_str()));
+
+    REFERRAL_REWARDS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (referrer, rewards) = item?;
+            Ok((String::from_utf8(referrer)?, rewards))
+        })
+        .collect()
+}
+
+/// Reads all bonds owned by a user in ascending order of bond id
+pub fn read_user_bonds(storage: &dyn Storage, owner: &str) -> StdResult<Vec<BondedRewardsDetails>> {
+    BONDED_REWARDS_DETAILS
+        .prefix(owner)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, bond) = item?;
+            Ok(bond)
+        })
+        .collect()
+}
+
+/// Reads the ids of the bonds owned by a user in ascending order, starting after `start_after`
+pub fn read_owner_bond_ids(
+    storage: &dyn Storage,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<u64>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|bond_id| Bound::exclusive(U64Key::new(bond_id)));
+
+    BONDED_REWARDS_DETAILS
+        .prefix(&owner)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, _) = item?;
+            parse_u64_key(&key)
+        })
+        .collect()
+}
+
+/// Reads bonds in ascending order of bond id, starting after `start_after`
+pub fn read_all_bonds(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BondedRewardsDetails>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|bond_id| Bound::exclusive(U64Key::new(bond_id)));
+
+    let mut bonds = vec![];
+    for item in BOND_OWNERS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+    {
+        let (key, owner) = item?;
+        let bond_id = parse_u64_key(&key)?;
+        if let Some(bond) =
+            BONDED_REWARDS_DETAILS.may_load(storage, (&owner, U64Key::new(bond_id)))?
+        {
+            bonds.push(bond);
+        }
+    }
+    Ok(bonds)
+}
+
+/// Reads the outstanding Fury of bonds issued before the swap opening, keyed by their day of maturity
+pub fn read_pre_opening_maturities(
+    storage: &dyn Storage,
+    swap_opening_date: Timestamp,
+) -> StdResult<BTreeMap<u64, MaturityBucket>> {
+    let mut buckets: BTreeMap<u64, MaturityBucket> = BTreeMap::new();
+    for item in PRE_OPENING_MATURITIES.range(storage, None, None, Order::Ascending) {
+        let (key, bucket) = item?;
+        let maturity = swap_opening_date.seconds() + parse_u64_key(&key)?;
+        buckets
+            .entry(maturity / SECONDS_PER_DAY)
+            .or_default()
+            .add(&bucket);
+    }
+    Ok(buckets)
+}
+
+/// Reads the outstanding Fury per day of maturity in ascending order of day, starting after `start_after`
+pub fn read_maturity_histogram(
+    storage: &dyn Storage,
+    swap_opening_date: Timestamp,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, MaturityBucket)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|day| Bound::exclusive(U64Key::new(day)));
+
+    let mut buckets = read_pre_opening_maturities(storage, swap_opening_date)?;
+    buckets.retain(|day, _| start_after.map_or(true, |start_day| *day > start_day));
+    for item in MATURITY_HISTOGRAM
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+    {
+        let (key, bucket) = item?;
+        buckets
+            .entry(parse_u64_key(&key)?)
+            .or_default()
+            .add(&bucket);
+    }
+    Ok(buckets.into_iter().take(limit).collect())
+}
+
+fn parse_u64_key(key: &[u8]) -> StdResult<u64> {
+    let bytes = key
+        .try_into()
+        .map_err(|_| StdError::generic_err("Invalid u64 key"))?;
+    Ok(u64::from_be_bytes(bytes))
+}